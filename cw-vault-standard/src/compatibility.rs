@@ -0,0 +1,167 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{StdError, StdResult};
+use semver::Version;
+
+/// The oldest version of the vault standard that the helpers in this crate can
+/// interact with. Older vaults return a numeric version from
+/// `VaultStandardInfo` and use incompatible messages.
+pub const MIN_COMPATIBLE_VERSION: &str = "0.4.0";
+
+/// The version of the vault standard in which the deprecated `amount` fields of
+/// `Deposit`, `Redeem`, `Unlock` and `ForceRedeem` are removed. Vaults using an
+/// older version still expect these fields to be set.
+pub const DEPRECATED_FIELDS_REMOVED_VERSION: &str = "0.5.0";
+
+/// The newest version of the vault standard whose messages are provided by
+/// this crate, in the [`v0_5`](crate::v0_5) module. Vaults in the same release
+/// line can be interacted with using those messages.
+pub const NEWEST_SUPPORTED_VERSION: &str = "0.5.0";
+
+/// The relationship between the version of the vault standard used by this
+/// crate and the version used by a vault.
+#[cw_serde]
+pub enum Compatibility {
+    /// The vault can be interacted with using the messages of this crate.
+    Compatible,
+    /// The vault can be interacted with using the messages of this crate, but
+    /// it still relies on fields that are deprecated, such as the `amount`
+    /// field of `Deposit`. These fields must be set correctly.
+    DeprecatedFieldsPresent,
+    /// The vault uses a version of the vault standard with breaking changes
+    /// relative to this crate, and should not be interacted with.
+    Breaking,
+}
+
+/// Parses a version of the vault standard as a semver string.
+pub fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version).map_err(|e| {
+        StdError::generic_err(format!("invalid vault standard version {version}: {e}"))
+    })
+}
+
+/// Returns true if the two versions are in the same semver compatibility
+/// range, i.e. share the same major version, or the same minor version for
+/// versions before 1.0.0.
+fn same_release_line(a: &Version, b: &Version) -> bool {
+    a.major == b.major && (a.major > 0 || a.minor == b.minor)
+}
+
+/// Classifies the compatibility between a client using `client_version` of the
+/// vault standard and a vault using `vault_version`.
+pub fn compare_versions(client_version: &str, vault_version: &str) -> StdResult<Compatibility> {
+    let client = parse_version(client_version)?;
+    let vault = parse_version(vault_version)?;
+
+    if vault < parse_version(MIN_COMPATIBLE_VERSION)?
+        || (vault > client && !same_release_line(&client, &vault))
+    {
+        return Ok(Compatibility::Breaking);
+    }
+    if vault < parse_version(DEPRECATED_FIELDS_REMOVED_VERSION)? {
+        return Ok(Compatibility::DeprecatedFieldsPresent);
+    }
+
+    Ok(Compatibility::Compatible)
+}
+
+/// Classifies the compatibility between the versions of the vault standard
+/// supported by this crate and a vault using `vault_version`. Vaults from
+/// [`MIN_COMPATIBLE_VERSION`] up to before [`DEPRECATED_FIELDS_REMOVED_VERSION`]
+/// can be interacted with using the messages from the crate root and have
+/// [`Compatibility::DeprecatedFieldsPresent`], vaults in the release line of
+/// [`NEWEST_SUPPORTED_VERSION`] can be interacted with using the messages from
+/// the [`v0_5`](crate::v0_5) module and are [`Compatibility::Compatible`], and
+/// all other vaults are [`Compatibility::Breaking`].
+pub fn check_compatibility(vault_version: &str) -> StdResult<Compatibility> {
+    compare_versions(NEWEST_SUPPORTED_VERSION, vault_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::VERSION;
+
+    #[test]
+    fn versions_before_min_compatible_version_are_breaking() {
+        assert_eq!(
+            check_compatibility("0.3.3").unwrap(),
+            Compatibility::Breaking
+        );
+        assert_eq!(
+            check_compatibility("0.2.0").unwrap(),
+            Compatibility::Breaking
+        );
+    }
+
+    #[test]
+    fn versions_with_deprecated_fields_are_supported() {
+        for version in ["0.4.0", "0.4.1", "0.4.9"] {
+            assert_eq!(
+                check_compatibility(version).unwrap(),
+                Compatibility::DeprecatedFieldsPresent
+            );
+        }
+    }
+
+    #[test]
+    fn current_version_is_supported() {
+        assert_ne!(
+            check_compatibility(VERSION).unwrap(),
+            Compatibility::Breaking
+        );
+    }
+
+    #[test]
+    fn newest_release_line_is_compatible() {
+        for version in ["0.5.0", "0.5.1", "0.5.12"] {
+            assert_eq!(
+                check_compatibility(version).unwrap(),
+                Compatibility::Compatible
+            );
+        }
+    }
+
+    #[test]
+    fn newer_release_lines_are_breaking() {
+        assert_eq!(
+            check_compatibility("0.6.0").unwrap(),
+            Compatibility::Breaking
+        );
+        assert_eq!(
+            check_compatibility("1.0.0").unwrap(),
+            Compatibility::Breaking
+        );
+    }
+
+    #[test]
+    fn clients_without_newer_messages_can_not_use_newer_vaults() {
+        assert_eq!(
+            compare_versions("0.4.1", "0.5.0").unwrap(),
+            Compatibility::Breaking
+        );
+        assert_eq!(
+            compare_versions("0.4.1", "0.4.2").unwrap(),
+            Compatibility::DeprecatedFieldsPresent
+        );
+    }
+
+    #[test]
+    fn stable_versions_compare_by_major_version() {
+        assert_eq!(
+            compare_versions("1.0.0", "1.3.0").unwrap(),
+            Compatibility::Compatible
+        );
+        assert_eq!(
+            compare_versions("1.3.0", "2.0.0").unwrap(),
+            Compatibility::Breaking
+        );
+    }
+
+    #[test]
+    fn invalid_versions_are_errors() {
+        check_compatibility("1").unwrap_err();
+        check_compatibility("v0.5.0").unwrap_err();
+        compare_versions("latest", "0.5.0").unwrap_err();
+    }
+}
@@ -0,0 +1,279 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{
+    to_json_binary, Coin, CosmosMsg, Decimal, StdError, StdResult, Timestamp, Uint128, WasmMsg,
+};
+
+use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
+
+/// The number of seconds in a (non-leap) year, used to annualize returns.
+pub const SECONDS_PER_YEAR: u64 = 365 * SECONDS_PER_DAY;
+/// The number of seconds in a day. Returns over windows shorter than a day are
+/// not compounded by [`apy_from_snapshots`].
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A snapshot of the exchange rate and total assets of the vault at a point in
+/// time.
+#[cw_serde]
+pub struct ExchangeRateSnapshot {
+    /// The block time at which the snapshot was taken.
+    pub time: Timestamp,
+    /// The amount of base tokens that can be exchanged for 1 unit of vault
+    /// tokens at the time of the snapshot. Equal to what
+    /// `VaultTokenExchangeRate` returned with the base token as quote denom.
+    pub exchange_rate: Decimal,
+    /// The total amount of assets managed by the vault at the time of the
+    /// snapshot, denominated in base tokens.
+    pub total_assets: Uint128,
+}
+
+/// Configuration of how the vault takes exchange rate snapshots.
+///
+/// Snapshots are stored in a ring buffer of `capacity` entries. Once the buffer
+/// is full, taking a new snapshot overwrites the oldest one. With the `storage`
+/// feature, the buffer is implemented by `storage::ExchangeRateSnapshots`.
+#[cw_serde]
+pub struct SnapshotConfig {
+    /// The minimum number of seconds between two consecutive snapshots.
+    pub interval_seconds: u64,
+    /// The maximum number of snapshots that are kept.
+    pub capacity: u32,
+}
+
+/// Additional ExecuteMsg variants for vaults that enable the Analytics
+/// extension.
+#[cw_serde]
+pub enum AnalyticsExecuteMsg {
+    /// Callable by vault admin to update the snapshot configuration. Lowering
+    /// the capacity drops the oldest snapshots that no longer fit.
+    UpdateSnapshotConfig {
+        /// The new minimum number of seconds between two snapshots.
+        interval_seconds: u64,
+        /// The new maximum number of snapshots that are kept.
+        capacity: u32,
+    },
+
+    /// Take a snapshot of the current exchange rate and total assets. Should
+    /// fail if less than `interval_seconds` have passed since the latest
+    /// snapshot. Vaults are also free to take snapshots as part of other
+    /// operations, such as deposits and redemptions.
+    TakeSnapshot {},
+}
+
+impl AnalyticsExecuteMsg {
+    /// Convert a [`AnalyticsExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&VaultStandardExecuteMsg::VaultExtension(
+                ExtensionExecuteMsg::Analytics(self),
+            ))?,
+            funds,
+        }
+        .into())
+    }
+}
+
+/// Additional QueryMsg variants for vaults that enable the Analytics
+/// extension.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum AnalyticsQueryMsg {
+    /// Returns the `SnapshotConfig` of the vault.
+    #[returns(SnapshotConfig)]
+    SnapshotConfig {},
+
+    /// Returns the latest `ExchangeRateSnapshot` taken at or before `time`.
+    /// Should return an error if no such snapshot is stored.
+    #[returns(ExchangeRateSnapshot)]
+    ExchangeRateAt {
+        /// The time to get the snapshot for.
+        time: Timestamp,
+    },
+
+    /// Returns a `Vec<ExchangeRateSnapshot>` containing the stored snapshots,
+    /// ordered by time.
+    #[returns(Vec<ExchangeRateSnapshot>)]
+    ExchangeRateHistory {
+        /// Return results only after this time
+        start_after: Option<Timestamp>,
+        /// Max amount of results to return
+        limit: Option<u32>,
+    },
+
+    /// Returns a `Decimal` containing the realized APR of the vault over the
+    /// last `window_seconds` seconds, computed with [`apr_from_snapshots`]
+    /// from the latest snapshot and the snapshot returned by `ExchangeRateAt`
+    /// for the start of the window.
+    #[returns(Decimal)]
+    RealizedApr {
+        /// The length of the window to compute the APR over, in seconds.
+        window_seconds: u64,
+    },
+}
+
+/// Returns the growth of the exchange rate between `start` and `end` and the
+/// number of seconds elapsed between them.
+fn growth_between(
+    start: &ExchangeRateSnapshot,
+    end: &ExchangeRateSnapshot,
+) -> StdResult<(Decimal, u64)> {
+    let elapsed = end.time.seconds().saturating_sub(start.time.seconds());
+    if elapsed == 0 {
+        return Err(StdError::generic_err(
+            "end snapshot must be taken at least one second after start snapshot",
+        ));
+    }
+    let growth = end
+        .exchange_rate
+        .checked_div(start.exchange_rate)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok((growth, elapsed))
+}
+
+/// Computes the annualized, non-compounded return between two snapshots from
+/// the growth of the exchange rate. Returns zero if the exchange rate did not
+/// increase, since a `Decimal` can not represent negative returns.
+pub fn apr_from_snapshots(
+    start: &ExchangeRateSnapshot,
+    end: &ExchangeRateSnapshot,
+) -> StdResult<Decimal> {
+    let (growth, elapsed) = growth_between(start, end)?;
+    let period_return = growth.saturating_sub(Decimal::one());
+
+    Ok(period_return.checked_mul(Decimal::from_ratio(SECONDS_PER_YEAR, elapsed))?)
+}
+
+/// Computes the annualized return between two snapshots, assuming the return of
+/// the period between them is compounded for each whole period that fits in a
+/// year. Periods longer than a year are not compounded, in which case this
+/// returns the same as [`apr_from_snapshots`]. Periods shorter than a day are
+/// not compounded either, since extrapolating the return of a few blocks over
+/// a year says little about the vault and quickly overflows a `Decimal`.
+pub fn apy_from_snapshots(
+    start: &ExchangeRateSnapshot,
+    end: &ExchangeRateSnapshot,
+) -> StdResult<Decimal> {
+    let (_, elapsed) = growth_between(start, end)?;
+    let apr = apr_from_snapshots(start, end)?;
+    if elapsed < SECONDS_PER_DAY {
+        return Ok(apr);
+    }
+    // At most 365 periods, so the cast can not truncate
+    let periods_per_year = (SECONDS_PER_YEAR / elapsed) as u32;
+
+    apy_from_apr(apr, periods_per_year)
+}
+
+/// Converts an APR into an APY, given the number of times per year that the
+/// returns are compounded. Zero compounding periods are treated as one. APYs
+/// too large to be represented saturate at `Decimal::MAX - 1`.
+pub fn apy_from_apr(apr: Decimal, compounding_periods: u32) -> StdResult<Decimal> {
+    let periods = compounding_periods.max(1);
+    let period_rate = apr
+        .checked_div(Decimal::from_ratio(periods, 1u128))
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(Decimal::one()
+        .saturating_add(period_rate)
+        .saturating_pow(periods)
+        .checked_sub(Decimal::one())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn snapshot(seconds: u64, exchange_rate: &str) -> ExchangeRateSnapshot {
+        ExchangeRateSnapshot {
+            time: Timestamp::from_seconds(seconds),
+            exchange_rate: dec(exchange_rate),
+            total_assets: Uint128::zero(),
+        }
+    }
+
+    #[test]
+    fn apr_is_annualized_return() {
+        let start = snapshot(0, "1");
+        let end = snapshot(SECONDS_PER_YEAR / 2, "1.01");
+        assert_eq!(
+            apr_from_snapshots(&start, &end).unwrap(),
+            Decimal::percent(2)
+        );
+    }
+
+    #[test]
+    fn apr_is_zero_for_decreasing_exchange_rate() {
+        let start = snapshot(0, "1.1");
+        let end = snapshot(SECONDS_PER_DAY, "1");
+        assert_eq!(apr_from_snapshots(&start, &end).unwrap(), Decimal::zero());
+    }
+
+    #[test]
+    fn snapshots_must_be_at_least_one_second_apart() {
+        let start = snapshot(10, "1");
+        let end = snapshot(10, "1.01");
+        apr_from_snapshots(&start, &end).unwrap_err();
+        apy_from_snapshots(&start, &end).unwrap_err();
+        apy_from_snapshots(&end, &start).unwrap_err();
+    }
+
+    #[test]
+    fn apy_compounds_whole_periods() {
+        let start = snapshot(0, "1");
+        let end = snapshot(SECONDS_PER_YEAR / 2, "1.01");
+        // 1.01^2 - 1
+        assert_eq!(apy_from_snapshots(&start, &end).unwrap(), dec("0.0201"));
+    }
+
+    #[test]
+    fn apy_is_apr_for_periods_longer_than_a_year() {
+        let start = snapshot(0, "1");
+        let end = snapshot(SECONDS_PER_YEAR * 2, "1.1");
+        assert_eq!(
+            apy_from_snapshots(&start, &end).unwrap(),
+            apr_from_snapshots(&start, &end).unwrap()
+        );
+    }
+
+    #[test]
+    fn apy_is_apr_for_periods_shorter_than_a_day() {
+        let start = snapshot(0, "1");
+        let end = snapshot(1, "1.01");
+        let apr = apr_from_snapshots(&start, &end).unwrap();
+        assert_eq!(apr, Decimal::from_ratio(SECONDS_PER_YEAR, 100u64));
+        assert_eq!(apy_from_snapshots(&start, &end).unwrap(), apr);
+    }
+
+    #[test]
+    fn apy_compounds_daily_returns() {
+        let start = snapshot(0, "1");
+        let end = snapshot(SECONDS_PER_DAY, "1.01");
+        let apy = apy_from_snapshots(&start, &end).unwrap();
+        // 1.01^365 - 1 ~= 36.78
+        assert!(apy > dec("36.78") && apy < dec("36.79"));
+    }
+
+    #[test]
+    fn apy_saturates_instead_of_overflowing() {
+        let start = snapshot(0, "1");
+        let end = snapshot(SECONDS_PER_DAY, "2");
+        assert_eq!(
+            apy_from_snapshots(&start, &end).unwrap(),
+            Decimal::MAX - Decimal::one()
+        );
+    }
+
+    #[test]
+    fn apy_from_apr_treats_zero_periods_as_one() {
+        let apr = Decimal::percent(10);
+        assert_eq!(apy_from_apr(apr, 0).unwrap(), apr);
+        assert_eq!(apy_from_apr(apr, 1).unwrap(), apr);
+        // 1.05^2 - 1
+        assert_eq!(apy_from_apr(apr, 2).unwrap(), dec("0.1025"));
+    }
+}
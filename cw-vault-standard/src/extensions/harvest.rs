@@ -0,0 +1,80 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, StdResult, Timestamp, Uint128, WasmMsg};
+
+use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
+
+/// Type for the event emitted on call to `Harvest`.
+pub const HARVEST_EVENT_TYPE: &str = "harvest";
+/// Key for the harvest id attribute in the "harvest" event that is emitted on
+/// call to `Harvest`.
+pub const HARVEST_ID_ATTR_KEY: &str = "harvest_id";
+
+/// A report of the result of a single harvest, persisted by the vault.
+#[cw_serde]
+pub struct HarvestReport {
+    /// The ID of the harvest. IDs are assigned incrementally, starting from 0.
+    pub id: u64,
+    /// The amount of base tokens gained in the harvest, after fees.
+    pub gain: Uint128,
+    /// The amount of base tokens lost since the previous harvest.
+    pub loss: Uint128,
+    /// The fees taken by the vault in the harvest, denominated in base tokens.
+    pub fees: Uint128,
+    /// The block time at which the harvest was executed.
+    pub timestamp: Timestamp,
+}
+
+/// Additional ExecuteMsg variants for vaults that enable the Harvest
+/// extension.
+#[cw_serde]
+pub enum HarvestExecuteMsg {
+    /// Claim pending rewards, compound them into the vault's position and
+    /// persist a [`HarvestReport`] with the result.
+    /// Emits an event with type `HARVEST_EVENT_TYPE` with an attribute with
+    /// key `HARVEST_ID_ATTR_KEY` containing the u64 ID of the report.
+    ///
+    /// Vaults may restrict who can call this, e.g. by also implementing the
+    /// Keeper extension and only allowing harvests through
+    /// `KeeperExecuteMsg::ExecuteJob`.
+    Harvest {},
+}
+
+impl HarvestExecuteMsg {
+    /// Convert a [`HarvestExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&VaultStandardExecuteMsg::VaultExtension(
+                ExtensionExecuteMsg::Harvest(self),
+            ))?,
+            funds,
+        }
+        .into())
+    }
+}
+
+/// Additional QueryMsg variants for vaults that enable the Harvest extension.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum HarvestQueryMsg {
+    /// Returns an `Option<HarvestReport>` containing the report of the most
+    /// recent harvest, or `None` if the vault has not been harvested yet.
+    #[returns(Option<HarvestReport>)]
+    LastHarvest {},
+
+    /// Returns a `Vec<HarvestReport>` containing the reports of previous
+    /// harvests, ordered by ID.
+    #[returns(Vec<HarvestReport>)]
+    HarvestHistory {
+        /// Return results only after this harvest ID
+        start_after: Option<u64>,
+        /// Max amount of results to return
+        limit: Option<u32>,
+    },
+
+    /// Returns a `Vec<Coin>` containing the rewards that have accrued to the
+    /// vault but have not been claimed yet, i.e. what would be harvested if
+    /// `Harvest` was called now.
+    #[returns(Vec<Coin>)]
+    PendingRewards {},
+}
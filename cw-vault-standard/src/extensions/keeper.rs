@@ -1,8 +1,37 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Decimal, StdError, StdResult,
+    Timestamp, WasmMsg,
+};
+use cw_utils::{Duration, Expiration};
 
 use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
 
+/// Type for the event emitted when a keeper is paid a bounty for executing a
+/// job. Contains an attribute with key `JOB_ID_ATTR_KEY`, an attribute with key
+/// `KEEPER_ATTR_KEY` and an attribute with key `REWARDS_ATTR_KEY` containing the
+/// coins of the bounty.
+pub const KEEPER_BOUNTY_PAID_EVENT_TYPE: &str = "keeper_bounty_paid";
+/// Type for the event emitted on call to `ClaimKeeperRewards`. Contains an
+/// attribute with key `KEEPER_ATTR_KEY` and an attribute with key
+/// `REWARDS_ATTR_KEY` containing the claimed coins.
+pub const KEEPER_REWARDS_CLAIMED_EVENT_TYPE: &str = "keeper_rewards_claimed";
+/// Key for the attribute containing the ID of the keeper job in the keeper
+/// events.
+pub const JOB_ID_ATTR_KEY: &str = "job_id";
+/// Key for the attribute containing the address of the keeper in the keeper
+/// events.
+pub const KEEPER_ATTR_KEY: &str = "keeper";
+/// Key for the attribute containing the paid or claimed coins in the keeper
+/// events.
+pub const REWARDS_ATTR_KEY: &str = "rewards";
+/// Type for the event emitted on call to `AddKeeperJob`. Contains an attribute
+/// with key `JOB_ID_ATTR_KEY` containing the ID of the new job.
+pub const KEEPER_JOB_ADDED_EVENT_TYPE: &str = "keeper_job_added";
+/// Type for the event emitted on call to `RemoveKeeperJob`. Contains an
+/// attribute with key `JOB_ID_ATTR_KEY`.
+pub const KEEPER_JOB_REMOVED_EVENT_TYPE: &str = "keeper_job_removed";
+
 /// A job that can be performed by a keeper.
 #[cw_serde]
 pub struct KeeperJob {
@@ -11,7 +40,231 @@ pub struct KeeperJob {
     /// whether only whitelisted keepers can execute the job or not
     pub whitelist: bool,
     /// A list of whitelisted addresses that can execute the job
+    #[deprecated(
+        since = "0.4.1",
+        note = "This field is always empty and will be removed in the next version. The \
+        whitelisted keepers of a job should instead be read with the paginated \
+        `WhitelistedKeepers` query."
+    )]
+    #[serde(default)]
     pub whitelisted_keepers: Vec<Addr>,
+    /// A short human readable name of the job
+    pub name: Option<String>,
+    /// A description of what the job does
+    pub description: Option<String>,
+    /// The minimum time or number of blocks between two executions of the
+    /// job. `None` if the job can be executed whenever it is ready.
+    pub interval: Option<Duration>,
+    /// The block at which the job was last executed, if it has been executed
+    pub last_executed: Option<KeeperJobExecution>,
+    /// The earliest point at which the job can be executed again. `None` if
+    /// the job is not rate limited.
+    pub next_executable_at: Option<Expiration>,
+    /// Optional job specific parameters, e.g. a swap route or a slippage
+    /// tolerance, for keepers that need them to execute the job
+    pub params: Option<Binary>,
+    /// The bounty paid to keepers for executing the job, if any
+    pub bounty: Option<KeeperBounty>,
+}
+
+impl KeeperJob {
+    /// Create a new KeeperJob without any metadata or schedule.
+    pub fn new(id: u64, whitelist: bool) -> Self {
+        #[allow(deprecated)]
+        Self {
+            id,
+            whitelist,
+            whitelisted_keepers: vec![],
+            name: None,
+            description: None,
+            interval: None,
+            last_executed: None,
+            next_executable_at: None,
+            params: None,
+            bounty: None,
+        }
+    }
+
+    /// Returns true if `next_executable_at` has been reached at `block`, or
+    /// if the job is not rate limited. Vaults may have additional conditions
+    /// for a job to be ready.
+    pub fn is_due(&self, block: &BlockInfo) -> bool {
+        self.next_executable_at
+            .map_or(true, |next| next.is_expired(block))
+    }
+
+    /// Records that the job was executed at `block`, and schedules its next
+    /// execution `interval` later.
+    pub fn record_execution(&mut self, block: &BlockInfo) {
+        self.last_executed = Some(KeeperJobExecution {
+            height: block.height,
+            time: block.time,
+        });
+        self.next_executable_at = self.interval.map(|interval| interval.after(block));
+    }
+}
+
+/// The block at which a keeper job was executed.
+#[cw_serde]
+pub struct KeeperJobExecution {
+    /// The height of the block
+    pub height: u64,
+    /// The time of the block
+    pub time: Timestamp,
+}
+
+impl KeeperJobExecution {
+    /// Returns true if at least `duration` has passed between this execution
+    /// and `block`.
+    pub fn has_elapsed(&self, duration: &Duration, block: &BlockInfo) -> bool {
+        match duration {
+            Duration::Height(blocks) => block.height >= self.height.saturating_add(*blocks),
+            Duration::Time(seconds) => block.time >= self.time.plus_seconds(*seconds),
+        }
+    }
+}
+
+/// The reward paid to a keeper for executing a job.
+#[cw_serde]
+pub enum KeeperReward {
+    /// A fixed amount of coins per execution.
+    Fixed {
+        /// The coins paid to the keeper
+        amount: Coin,
+    },
+    /// A share of the yield harvested by the execution, for jobs that harvest
+    /// or compound rewards.
+    YieldShare {
+        /// The share of the harvested yield paid to the keeper, e.g. `0.01`
+        /// for 1%
+        share: Decimal,
+    },
+}
+
+/// A bounty paid to keepers for executing a job, which lets permissionless
+/// keepers be compensated for their gas costs. Bounties are accrued by the
+/// vault and can be claimed with [`KeeperExecuteMsg::ClaimKeeperRewards`].
+#[cw_serde]
+pub struct KeeperBounty {
+    /// The reward paid per execution
+    pub reward: KeeperReward,
+    /// The minimum time or number of blocks since the last execution of the
+    /// job for the bounty to be paid, so that the job cannot be executed over
+    /// and over to farm bounties.
+    pub min_interval: Option<Duration>,
+    /// The time or number of blocks a keeper has to wait after being paid a
+    /// bounty for the job before it can be paid a bounty for it again.
+    pub cooldown: Option<Duration>,
+}
+
+impl KeeperBounty {
+    /// Returns an error if the bounty is invalid, i.e. if a `YieldShare`
+    /// reward pays out more than the harvested yield.
+    pub fn validate(&self) -> StdResult<()> {
+        match &self.reward {
+            KeeperReward::YieldShare { share } if *share > Decimal::one() => Err(
+                StdError::generic_err(format!("keeper yield share {share} is more than one")),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns true if the bounty should be paid to a keeper executing `job` at
+    /// `block`. `keeper_last_paid` is the execution for which the keeper was
+    /// last paid a bounty for the job, if any. Must be called before the
+    /// execution is recorded on the job.
+    pub fn is_payable(
+        &self,
+        job: &KeeperJob,
+        keeper_last_paid: Option<&KeeperJobExecution>,
+        block: &BlockInfo,
+    ) -> bool {
+        let min_interval_passed = match (&self.min_interval, &job.last_executed) {
+            (Some(min_interval), Some(last)) => last.has_elapsed(min_interval, block),
+            _ => true,
+        };
+        let cooldown_passed = match (&self.cooldown, keeper_last_paid) {
+            (Some(cooldown), Some(last)) => last.has_elapsed(cooldown, block),
+            _ => true,
+        };
+        min_interval_passed && cooldown_passed
+    }
+
+    /// Returns the coins to pay for an execution that harvested `harvested`.
+    /// `harvested` is ignored for fixed rewards. Returns an error if a share of
+    /// the harvest overflows, which can only happen for shares above one.
+    pub fn payout(&self, harvested: &[Coin]) -> StdResult<Vec<Coin>> {
+        match &self.reward {
+            KeeperReward::Fixed { amount } => Ok(vec![amount.clone()]),
+            KeeperReward::YieldShare { share } => {
+                let mut payout = vec![];
+                for coin in harvested {
+                    let amount = coin.amount.checked_mul_floor(*share).map_err(|err| {
+                        StdError::generic_err(format!("keeper bounty of {coin}: {err}"))
+                    })?;
+                    if !amount.is_zero() {
+                        payout.push(Coin::new(amount.u128(), &coin.denom));
+                    }
+                }
+                Ok(payout)
+            }
+        }
+    }
+}
+
+/// Response of [`KeeperQueryMsg::KeeperJobStatus`], which can be used by
+/// off-chain keepers to schedule the execution of a job.
+#[cw_serde]
+pub struct KeeperJobStatus {
+    /// The ID of the job
+    pub job_id: u64,
+    /// Whether the job can currently be executed
+    pub ready: bool,
+    /// The block at which the job was last executed, if it has been executed
+    pub last_executed: Option<KeeperJobExecution>,
+    /// The earliest point at which the job can be executed again. `None` if
+    /// the job is not rate limited.
+    pub next_executable_at: Option<Expiration>,
+}
+
+/// The configuration of a new keeper job, passed to
+/// [`KeeperExecuteMsg::AddKeeperJob`]. See [`KeeperJob`] for a description of
+/// the fields.
+#[cw_serde]
+pub struct KeeperJobConfig {
+    /// whether only whitelisted keepers can execute the job or not
+    pub whitelist: bool,
+    /// A short human readable name of the job
+    pub name: Option<String>,
+    /// A description of what the job does
+    pub description: Option<String>,
+    /// The minimum time or number of blocks between two executions of the job
+    pub interval: Option<Duration>,
+    /// Optional job specific parameters
+    pub params: Option<Binary>,
+    /// The bounty paid to keepers for executing the job, if any
+    pub bounty: Option<KeeperBounty>,
+}
+
+impl KeeperJobConfig {
+    /// Returns an error if the bounty of the configuration is invalid, see
+    /// [`KeeperBounty::validate`].
+    pub fn validate(&self) -> StdResult<()> {
+        self.bounty.as_ref().map_or(Ok(()), KeeperBounty::validate)
+    }
+
+    /// Returns a new [`KeeperJob`] with ID `id` and this configuration, which
+    /// has not been executed yet and has no whitelisted keepers.
+    pub fn into_job(self, id: u64) -> KeeperJob {
+        KeeperJob {
+            name: self.name,
+            description: self.description,
+            interval: self.interval,
+            params: self.params,
+            bounty: self.bounty,
+            ..KeeperJob::new(id, self.whitelist)
+        }
+    }
 }
 
 /// Additional ExecuteMsg variants for vaults that enable the Keeper extension.
@@ -32,13 +285,50 @@ pub enum KeeperExecuteMsg {
         /// The address of the keeper to blacklist
         keeper: String,
     },
+    /// Callable by vault admin to add and remove several keepers at once,
+    /// either to the whitelist of a job or to the global keeper whitelist.
+    /// Keepers on the global whitelist can execute all jobs, including jobs
+    /// with a whitelist. Keepers are added before they are removed.
+    UpdateKeeperWhitelist {
+        /// The ID of the job to update the whitelist of, or `None` to update
+        /// the global keeper whitelist
+        job_id: Option<u64>,
+        /// Addresses to add to the whitelist
+        add: Vec<String>,
+        /// Addresses to remove from the whitelist
+        remove: Vec<String>,
+    },
+    /// Callable by vault admin to add a keeper job. The vault assigns the ID
+    /// of the job and emits an event with type `KEEPER_JOB_ADDED_EVENT_TYPE`.
+    AddKeeperJob {
+        /// The configuration of the job
+        config: KeeperJobConfig,
+    },
+    /// Callable by vault admin to remove a keeper job. Emits an event with type
+    /// `KEEPER_JOB_REMOVED_EVENT_TYPE`.
+    RemoveKeeperJob {
+        /// The ID of the job to remove
+        job_id: u64,
+    },
     /// Execute a keeper job. Should only be able to be called if
-    /// [`KeeperQueryMsg::KeeperJobReady`] returns true, and only by whitelisted
-    /// keepers if the whitelist bool on the KeeperJob is set to true.
+    /// [`KeeperQueryMsg::KeeperJobReady`] returns true, and only by keepers
+    /// on the whitelist of the job or the global keeper whitelist if the
+    /// whitelist bool on the KeeperJob is set to true. Vaults
+    /// should update `last_executed` and `next_executable_at` of the job, e.g.
+    /// with [`KeeperJob::record_execution`]. If the job has a bounty that is
+    /// payable according to [`KeeperBounty::is_payable`], the bounty should be
+    /// accrued to the caller and an event with type
+    /// `KEEPER_BOUNTY_PAID_EVENT_TYPE` emitted.
     ExecuteJob {
         /// The ID of the job to execute
         job_id: u64,
     },
+    /// Sends the keeper rewards accrued by the caller to `recipient`. Emits an
+    /// event with type `KEEPER_REWARDS_CLAIMED_EVENT_TYPE`.
+    ClaimKeeperRewards {
+        /// The address to send the rewards to. Defaults to the caller.
+        recipient: Option<String>,
+    },
 }
 
 impl KeeperExecuteMsg {
@@ -59,14 +349,31 @@ impl KeeperExecuteMsg {
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum KeeperQueryMsg {
-    /// Returns [`Vec<KeeperJob>`]
+    /// Returns [`Vec<KeeperJob>`], ordered by ID. The whitelisted keepers of
+    /// the jobs are not included and should be queried with
+    /// [`KeeperQueryMsg::WhitelistedKeepers`].
     #[returns(Vec<KeeperJob>)]
-    KeeperJobs {},
-    /// Returns [`Vec<Addr>`]
+    KeeperJobs {
+        /// Return results only after this job ID
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_after: Option<u64>,
+        /// Max amount of results to return
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+    },
+    /// Returns [`Vec<Addr>`], ordered by address
     #[returns(Vec<Addr>)]
     WhitelistedKeepers {
-        /// The ID of the job to get the whitelisted keepers for
-        job_id: u64,
+        /// The ID of the job to get the whitelisted keepers for, or `None` to
+        /// get the global keeper whitelist
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        job_id: Option<u64>,
+        /// Return results only after this address
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_after: Option<String>,
+        /// Max amount of results to return
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
     },
     /// Returns bool, whether the keeper job can be executed or not
     #[returns(bool)]
@@ -74,4 +381,182 @@ pub enum KeeperQueryMsg {
         /// The ID of the job to check whether it is ready to be executed
         job_id: u64,
     },
+    /// Returns [`KeeperJobStatus`], with the readiness and schedule of the
+    /// keeper job
+    #[returns(KeeperJobStatus)]
+    KeeperJobStatus {
+        /// The ID of the job to get the status of
+        job_id: u64,
+    },
+    /// Returns [`Vec<Coin>`], the rewards accrued by the keeper that can be
+    /// claimed with [`KeeperExecuteMsg::ClaimKeeperRewards`]
+    #[returns(Vec<Coin>)]
+    KeeperRewards {
+        /// The address of the keeper
+        keeper: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{coin, coins, from_json, to_json_string};
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn bounty(reward: KeeperReward) -> KeeperBounty {
+        KeeperBounty {
+            reward,
+            min_interval: Some(Duration::Time(100)),
+            cooldown: Some(Duration::Height(50)),
+        }
+    }
+
+    fn execution(block: &BlockInfo) -> KeeperJobExecution {
+        KeeperJobExecution {
+            height: block.height,
+            time: block.time,
+        }
+    }
+
+    #[test]
+    fn fixed_payout_ignores_harvest() {
+        let bounty = bounty(KeeperReward::Fixed {
+            amount: coin(10, "uosmo"),
+        });
+        assert_eq!(bounty.payout(&[]).unwrap(), coins(10, "uosmo"));
+        assert_eq!(
+            bounty.payout(&coins(1000, "uatom")).unwrap(),
+            coins(10, "uosmo")
+        );
+    }
+
+    #[test]
+    fn yield_share_payout_is_share_of_harvest() {
+        let bounty = bounty(KeeperReward::YieldShare { share: dec("0.01") });
+        assert_eq!(
+            bounty
+                .payout(&[coin(1000, "uatom"), coin(50, "uosmo")])
+                .unwrap(),
+            coins(10, "uatom")
+        );
+        assert_eq!(bounty.payout(&[]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn yield_share_payout_overflow_is_an_error() {
+        let bounty = bounty(KeeperReward::YieldShare { share: dec("2") });
+        bounty.payout(&[coin(u128::MAX, "uatom")]).unwrap_err();
+        assert_eq!(
+            bounty.payout(&[coin(u128::MAX / 2, "uatom")]).unwrap(),
+            coins(u128::MAX - 1, "uatom")
+        );
+    }
+
+    #[test]
+    fn yield_share_must_not_exceed_one() {
+        bounty(KeeperReward::YieldShare { share: dec("1") })
+            .validate()
+            .unwrap();
+        bounty(KeeperReward::YieldShare { share: dec("1.01") })
+            .validate()
+            .unwrap_err();
+
+        let config = KeeperJobConfig {
+            whitelist: false,
+            name: None,
+            description: None,
+            interval: None,
+            params: None,
+            bounty: Some(bounty(KeeperReward::YieldShare { share: dec("2") })),
+        };
+        config.validate().unwrap_err();
+        KeeperJobConfig {
+            bounty: None,
+            ..config
+        }
+        .validate()
+        .unwrap();
+    }
+
+    #[test]
+    fn bounty_is_payable_after_min_interval() {
+        let bounty = bounty(KeeperReward::YieldShare { share: dec("0.01") });
+        let mut block = mock_env().block;
+        let mut job = KeeperJob::new(0, false);
+        assert!(bounty.is_payable(&job, None, &block));
+
+        job.record_execution(&block);
+        block.time = block.time.plus_seconds(99);
+        assert!(!bounty.is_payable(&job, None, &block));
+        block.time = block.time.plus_seconds(1);
+        assert!(bounty.is_payable(&job, None, &block));
+    }
+
+    #[test]
+    fn bounty_is_payable_after_keeper_cooldown() {
+        let bounty = bounty(KeeperReward::YieldShare { share: dec("0.01") });
+        let mut block = mock_env().block;
+        let job = KeeperJob::new(0, false);
+        let last_paid = execution(&block);
+
+        block.height += 49;
+        assert!(!bounty.is_payable(&job, Some(&last_paid), &block));
+        block.height += 1;
+        assert!(bounty.is_payable(&job, Some(&last_paid), &block));
+    }
+
+    #[test]
+    fn record_execution_schedules_next_execution() {
+        let block = mock_env().block;
+        let mut job = KeeperJob {
+            interval: Some(Duration::Height(10)),
+            ..KeeperJob::new(0, false)
+        };
+        assert!(job.is_due(&block));
+        job.record_execution(&block);
+        assert_eq!(job.last_executed, Some(execution(&block)));
+        assert_eq!(
+            job.next_executable_at,
+            Some(Expiration::AtHeight(block.height + 10))
+        );
+        assert!(!job.is_due(&block));
+    }
+
+    #[test]
+    fn unpaginated_queries_are_serialized_as_before() {
+        let cases = [
+            (
+                KeeperQueryMsg::KeeperJobs {
+                    start_after: None,
+                    limit: None,
+                },
+                r#"{"keeper_jobs":{}}"#,
+            ),
+            (
+                KeeperQueryMsg::WhitelistedKeepers {
+                    job_id: Some(1),
+                    start_after: None,
+                    limit: None,
+                },
+                r#"{"whitelisted_keepers":{"job_id":1}}"#,
+            ),
+            (
+                KeeperQueryMsg::WhitelistedKeepers {
+                    job_id: None,
+                    start_after: Some("keeper".to_string()),
+                    limit: Some(10),
+                },
+                r#"{"whitelisted_keepers":{"start_after":"keeper","limit":10}}"#,
+            ),
+        ];
+        for (msg, json) in cases {
+            assert_eq!(to_json_string(&msg).unwrap(), json);
+            assert_eq!(from_json::<KeeperQueryMsg>(json).unwrap(), msg);
+        }
+    }
 }
@@ -1,5 +1,8 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, StdResult, Uint128, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, BlockInfo, Coin, CosmosMsg, Decimal, StdError, StdResult, Timestamp,
+    Uint128, WasmMsg,
+};
 use cw_utils::{Duration, Expiration};
 
 use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
@@ -9,6 +12,43 @@ pub const UNLOCKING_POSITION_CREATED_EVENT_TYPE: &str = "unlocking_position_crea
 /// Key for the lockup id attribute in the "unlocking position created" event
 /// that is emitted on call to `Unlock`.
 pub const UNLOCKING_POSITION_ATTR_KEY: &str = "lockup_id";
+/// Type for the event emitted on call to `TransferUnlockingPosition`. Contains
+/// an attribute with key `UNLOCKING_POSITION_ATTR_KEY` and an attribute with
+/// key `RECIPIENT_ATTR_KEY`.
+pub const UNLOCKING_POSITION_TRANSFERRED_EVENT_TYPE: &str = "unlocking_position_transferred";
+/// Type for the event emitted on call to `SplitUnlockingPosition`. Contains
+/// an attribute with key `UNLOCKING_POSITION_ATTR_KEY` containing the ID of
+/// the split position and an attribute with key `NEW_LOCKUP_ID_ATTR_KEY`
+/// containing the ID of the newly created position.
+pub const UNLOCKING_POSITION_SPLIT_EVENT_TYPE: &str = "unlocking_position_split";
+/// Type for the event emitted on call to `MergeUnlockingPositions`. Contains
+/// an attribute with key `UNLOCKING_POSITION_ATTR_KEY` containing the ID of
+/// the merged position and an attribute with key `MERGED_LOCKUP_IDS_ATTR_KEY`
+/// containing a comma separated list of the IDs that were merged into it.
+pub const UNLOCKING_POSITIONS_MERGED_EVENT_TYPE: &str = "unlocking_positions_merged";
+/// Type for the event emitted on call to `EarlyWithdraw`. Contains an
+/// attribute with key `UNLOCKING_POSITION_ATTR_KEY` and an attribute with key
+/// `PENALTY_ATTR_KEY` containing the amount of base tokens taken as penalty.
+pub const EARLY_WITHDRAW_EVENT_TYPE: &str = "unlocking_position_early_withdrawn";
+/// Key for the attribute containing the penalty in the "unlocking position
+/// early withdrawn" event.
+pub const PENALTY_ATTR_KEY: &str = "penalty";
+/// Type for the event emitted on call to `CancelUnlock`. Contains an attribute
+/// with key `UNLOCKING_POSITION_ATTR_KEY` and an attribute with key
+/// `VAULT_TOKEN_AMOUNT_ATTR_KEY` containing the amount of vault tokens minted.
+pub const UNLOCK_CANCELLED_EVENT_TYPE: &str = "unlock_cancelled";
+/// Key for the attribute containing the amount of vault tokens minted in the
+/// "unlock cancelled" event.
+pub const VAULT_TOKEN_AMOUNT_ATTR_KEY: &str = "vault_token_amount";
+/// Key for the attribute containing the address that received an unlocking
+/// position in the "unlocking position transferred" event.
+pub const RECIPIENT_ATTR_KEY: &str = "recipient";
+/// Key for the attribute containing the ID of the position created in the
+/// "unlocking position split" event.
+pub const NEW_LOCKUP_ID_ATTR_KEY: &str = "new_lockup_id";
+/// Key for the attribute containing the IDs of the positions merged in the
+/// "unlocking positions merged" event.
+pub const MERGED_LOCKUP_IDS_ATTR_KEY: &str = "merged_lockup_ids";
 
 /// Additional ExecuteMsg variants for vaults that enable the Lockup extension.
 #[cw_serde]
@@ -30,6 +70,11 @@ pub enum LockupExecuteMsg {
             of vault tokens should instead be read from the actual amount of sent vault tokens."
         )]
         amount: Uint128,
+        /// The ID of the lockup tier to unlock with, as returned by the
+        /// `LockupTiers` query. If not set, the default lockup duration of the
+        /// vault is used. Should fail if the tier does not exist.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tier: Option<u64>,
     },
 
     /// EmergencyUnlock is called to initiate unlocking a locked position held
@@ -52,6 +97,89 @@ pub enum LockupExecuteMsg {
         /// The ID of the expired lockup to withdraw from.
         lockup_id: u64,
     },
+
+    /// Withdraw all unlocking positions of the caller that have finished
+    /// unlocking. Should not fail if there are no such positions.
+    WithdrawAllUnlocked {
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+
+    /// Withdraw several unlocking positions that have finished unlocking.
+    /// Should fail if any of the positions is not owned by the caller or has
+    /// not finished unlocking.
+    WithdrawUnlockedBatch {
+        /// The IDs of the expired lockups to withdraw from.
+        lockup_ids: Vec<u64>,
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+
+    /// Transfer an unlocking position owned by the caller to `recipient`. The
+    /// position keeps its ID, amount and `release_at`.
+    /// Emits an event with type `UNLOCKING_POSITION_TRANSFERRED_EVENT_TYPE`.
+    /// Not supported by vaults that enable the LockupNft extension, in which
+    /// the owner of a position is the owner of its cw721 token and positions
+    /// are transferred by transferring the token.
+    TransferUnlockingPosition {
+        /// The ID of the unlocking position to transfer.
+        lockup_id: u64,
+        /// The address which should become the owner of the position.
+        recipient: String,
+    },
+
+    /// Split `amount` base tokens off an unlocking position owned by the
+    /// caller into a new position, also owned by the caller, with the same
+    /// `release_at`. Should fail if `amount` is zero or not less than the
+    /// amount of the position.
+    /// Emits an event with type `UNLOCKING_POSITION_SPLIT_EVENT_TYPE`.
+    SplitUnlockingPosition {
+        /// The ID of the unlocking position to split.
+        lockup_id: u64,
+        /// The amount of base tokens to move to the new position.
+        amount: Uint128,
+    },
+
+    /// Merge unlocking positions owned by the caller into the first position
+    /// in `ids`, removing the others. The merged position is released at the
+    /// latest `release_at` of the merged positions, so that merging can never
+    /// shorten a lockup. Should fail if fewer than two IDs are given.
+    /// Emits an event with type `UNLOCKING_POSITIONS_MERGED_EVENT_TYPE`.
+    MergeUnlockingPositions {
+        /// The IDs of the unlocking positions to merge.
+        ids: Vec<u64>,
+    },
+
+    /// Cancel an unlocking position owned by the caller, removing it and
+    /// minting vault tokens to the caller in return, as if the position had
+    /// never been unlocked. The amount of vault tokens depends on the
+    /// `CancelUnlockMode` of the vault. Should fail if the position has
+    /// finished unlocking.
+    /// Emits an event with type `UNLOCK_CANCELLED_EVENT_TYPE`.
+    CancelUnlock {
+        /// The ID of the unlocking position to cancel.
+        lockup_id: u64,
+    },
+
+    /// Withdraw an unlocking position owned by the caller before it has
+    /// finished unlocking, in exchange for a penalty. The penalty is computed
+    /// from the `PenaltySchedule` of the vault and handled according to its
+    /// `PenaltyDestination`, and the remaining base tokens are sent to
+    /// `recipient`. Unlike `ForceWithdrawUnlocking` of the ForceUnlock
+    /// extension, this can be called by any owner of a position.
+    /// Emits an event with type `EARLY_WITHDRAW_EVENT_TYPE`.
+    EarlyWithdraw {
+        /// The ID of the unlocking position to withdraw.
+        lockup_id: u64,
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
 }
 
 impl LockupExecuteMsg {
@@ -84,6 +212,15 @@ pub enum LockupQueryMsg {
         limit: Option<u32>,
     },
 
+    /// Returns a `ClaimableUnlockedResponse` containing the unlocking
+    /// positions of `owner` that have finished unlocking and can be withdrawn
+    /// with `WithdrawAllUnlocked`.
+    #[returns(ClaimableUnlockedResponse)]
+    ClaimableUnlocked {
+        /// The address of the owner of the lockups
+        owner: String,
+    },
+
     /// Returns an `UnlockingPosition` info about a specific lockup, by owner
     /// and ID.
     #[returns(UnlockingPosition)]
@@ -92,9 +229,238 @@ pub enum LockupQueryMsg {
         lockup_id: u64,
     },
 
+    /// Returns a `Vec<UnlockingPosition>` containing the unclaimed lockup
+    /// positions of all owners, ordered by ID.
+    #[returns(Vec<UnlockingPosition>)]
+    AllUnlockingPositions {
+        /// Return results only after this lockup_id
+        start_after: Option<u64>,
+        /// Max amount of results to return
+        limit: Option<u32>,
+    },
+
+    /// Returns a `Vec<UnlockingPosition>` containing the unclaimed lockup
+    /// positions of all owners that finish unlocking at or before `time`,
+    /// ordered by release time and then by ID. Positions with a `release_at`
+    /// in block height are not returned, since the time at which they finish
+    /// unlocking is not known. Vaults with a lockup duration in blocks should
+    /// use `AllUnlockingPositions` instead.
+    #[returns(Vec<UnlockingPosition>)]
+    UnlockingPositionsMaturingBefore {
+        /// The time at or before which the positions finish unlocking.
+        time: Timestamp,
+        /// Return results only after the position with this release time and
+        /// lockup_id, i.e. the last position of the previous page.
+        start_after: Option<(Timestamp, u64)>,
+        /// Max amount of results to return
+        limit: Option<u32>,
+    },
+
+    /// Returns a `Uint128` containing the sum of the base tokens of all
+    /// unclaimed lockup positions.
+    #[returns(Uint128)]
+    TotalUnlocking {},
+
     /// Returns `cw_utils::Duration` duration of the lockup of the vault.
     #[returns(Duration)]
     LockupDuration {},
+
+    /// Returns a `Vec<LockupTier>` containing the lockup tiers offered by the
+    /// vault, ordered by ID. Vaults that do not offer tiers should return an
+    /// empty list.
+    #[returns(Vec<LockupTier>)]
+    LockupTiers {},
+
+    /// Returns an `EarlyWithdrawPreview` containing the amount of base tokens
+    /// that would be received and the penalty that would be taken if the
+    /// unlocking position with ID `lockup_id` was withdrawn with
+    /// `EarlyWithdraw` in the current block.
+    #[returns(EarlyWithdrawPreview)]
+    PreviewEarlyWithdraw {
+        /// The ID of the unlocking position.
+        lockup_id: u64,
+    },
+
+    /// Returns the `CancelUnlockMode` of the vault, or an error if the vault
+    /// does not allow cancelling unlocks.
+    #[returns(CancelUnlockMode)]
+    CancelUnlockMode {},
+
+    /// Returns the `EarlyWithdrawConfig` of the vault, or an error if the
+    /// vault does not allow early withdrawals.
+    #[returns(EarlyWithdrawConfig)]
+    EarlyWithdrawConfig {},
+}
+
+/// How a vault determines the amount of vault tokens minted when an unlocking
+/// position is cancelled with `CancelUnlock`.
+#[cw_serde]
+pub enum CancelUnlockMode {
+    /// Vault tokens are minted for the base tokens of the position at the
+    /// current exchange rate, as in a deposit.
+    CurrentExchangeRate,
+    /// The same amount of vault tokens is minted as was burned when the
+    /// position was created, so the caller gets back their original shares.
+    OriginalShares,
+}
+
+/// The penalty of withdrawing an unlocking position early, as a fraction of
+/// its base tokens, depending on how much of the lockup remains. Time is
+/// measured in the unit of `release_at` of the position: seconds or blocks.
+#[cw_serde]
+pub enum PenaltySchedule {
+    /// The penalty decreases linearly from `max_penalty`, when the entire
+    /// lockup remains, to zero at `release_at`.
+    Linear {
+        /// The penalty when the entire lockup remains.
+        max_penalty: Decimal,
+    },
+    /// The penalty is that of the step with the largest `min_remaining` that
+    /// is not more than the remaining lockup, or zero if there is no such
+    /// step.
+    Step {
+        /// The steps of the schedule.
+        steps: Vec<PenaltyStep>,
+    },
+}
+
+/// A step of a [`PenaltySchedule::Step`] schedule.
+#[cw_serde]
+pub struct PenaltyStep {
+    /// The minimum remaining lockup, in seconds or blocks, for this step to
+    /// apply.
+    pub min_remaining: u64,
+    /// The penalty, as a fraction of the base tokens of the position.
+    pub penalty: Decimal,
+}
+
+impl PenaltySchedule {
+    /// Returns the penalty, as a fraction of the base tokens of a position,
+    /// for withdrawing a position with a lockup of `duration` of which
+    /// `remaining` remains. The penalty is zero once nothing remains, whatever
+    /// the schedule, and is capped at one.
+    pub fn penalty_rate(&self, remaining: u64, duration: u64) -> Decimal {
+        if remaining == 0 {
+            return Decimal::zero();
+        }
+        let rate = match self {
+            PenaltySchedule::Linear { max_penalty } => {
+                if duration == 0 {
+                    Decimal::zero()
+                } else {
+                    max_penalty
+                        .checked_mul(Decimal::from_ratio(remaining.min(duration), duration))
+                        .unwrap_or(Decimal::one())
+                }
+            }
+            PenaltySchedule::Step { steps } => steps
+                .iter()
+                .filter(|step| step.min_remaining <= remaining)
+                .max_by_key(|step| step.min_remaining)
+                .map(|step| step.penalty)
+                .unwrap_or_default(),
+        };
+        rate.min(Decimal::one())
+    }
+}
+
+/// What happens to the base tokens taken as penalty by `EarlyWithdraw`.
+#[cw_serde]
+pub enum PenaltyDestination {
+    /// The penalty is kept in the vault, increasing the amount of base tokens
+    /// per vault token for the remaining depositors.
+    RemainingDepositors,
+    /// The penalty is sent to a treasury address.
+    Treasury {
+        /// The address of the treasury.
+        address: String,
+    },
+    /// The penalty is burned.
+    Burn,
+}
+
+/// The configuration of early withdrawals of a vault.
+#[cw_serde]
+pub struct EarlyWithdrawConfig {
+    /// The penalty schedule of early withdrawals.
+    pub schedule: PenaltySchedule,
+    /// What happens to the penalty of early withdrawals.
+    pub destination: PenaltyDestination,
+}
+
+/// Response of the `PreviewEarlyWithdraw` query.
+#[cw_serde]
+pub struct EarlyWithdrawPreview {
+    /// The amount of base tokens that would be received.
+    pub net_amount: Uint128,
+    /// The amount of base tokens that would be taken as penalty.
+    pub penalty: Uint128,
+}
+
+/// Computes the result of withdrawing `position` early in `block`, given the
+/// `duration` of its lockup and the penalty `schedule`. Positions that have
+/// finished unlocking have no penalty. Returns an error if the units of
+/// `duration` and `position.release_at` differ, or if the position never
+/// releases.
+pub fn preview_early_withdraw(
+    position: &UnlockingPosition,
+    duration: &Duration,
+    schedule: &PenaltySchedule,
+    block: &BlockInfo,
+) -> StdResult<EarlyWithdrawPreview> {
+    let (remaining, duration) = match (position.release_at, duration) {
+        (Expiration::AtTime(release_at), Duration::Time(duration)) => (
+            release_at.seconds().saturating_sub(block.time.seconds()),
+            *duration,
+        ),
+        (Expiration::AtHeight(release_at), Duration::Height(duration)) => {
+            (release_at.saturating_sub(block.height), *duration)
+        }
+        _ => {
+            return Err(StdError::generic_err(
+                "release_at of unlocking position does not match lockup duration",
+            ))
+        }
+    };
+
+    let penalty = position
+        .base_token_amount
+        .mul_floor(schedule.penalty_rate(remaining, duration));
+
+    Ok(EarlyWithdrawPreview {
+        net_amount: position.base_token_amount - penalty,
+        penalty,
+    })
+}
+
+/// Response of the `ClaimableUnlocked` query.
+#[cw_serde]
+pub struct ClaimableUnlockedResponse {
+    /// The IDs of the unlocking positions that have finished unlocking.
+    pub lockup_ids: Vec<u64>,
+    /// The sum of the base tokens of these positions.
+    pub base_token_amount: Uint128,
+}
+
+/// A lockup tier, letting users choose a longer lockup in exchange for a
+/// larger share of rewards.
+#[cw_serde]
+pub struct LockupTier {
+    /// The ID of the tier, to pass in `Unlock`.
+    pub id: u64,
+    /// The duration of the lockup of positions in this tier.
+    pub duration: Duration,
+    /// The multiplier applied to the amount of positions in this tier when
+    /// distributing rewards.
+    pub multiplier: Decimal,
+}
+
+impl LockupTier {
+    /// Returns the weight of `amount` base tokens locked in this tier, for
+    /// reward distribution.
+    pub fn effective_weight(&self, amount: Uint128) -> Uint128 {
+        amount.mul_floor(self.multiplier)
+    }
 }
 
 /// Info about a currenly unlocking position.
@@ -109,4 +475,336 @@ pub struct UnlockingPosition {
     pub release_at: Expiration,
     /// The amount of base tokens that are being unlocked.
     pub base_token_amount: Uint128,
+    /// The ID of the lockup tier of the position, or `None` if the position
+    /// uses the default lockup duration of the vault.
+    pub tier: Option<u64>,
+    /// The amount of vault tokens that were burned to create the position.
+    /// Zero in responses of vaults that do not report it.
+    #[serde(default)]
+    pub vault_token_amount: Uint128,
+    /// The block time at which the position was created. Zero in responses
+    /// of vaults that do not report it.
+    #[serde(default)]
+    pub created_at: Timestamp,
+}
+
+/// Returns the weight of `position` for reward distribution: its amount times
+/// the multiplier of its tier. Positions without a tier have a multiplier of
+/// one. Returns an error if the tier of the position is not in `tiers`.
+pub fn effective_weight(position: &UnlockingPosition, tiers: &[LockupTier]) -> StdResult<Uint128> {
+    match position.tier {
+        None => Ok(position.base_token_amount),
+        Some(tier_id) => tiers
+            .iter()
+            .find(|tier| tier.id == tier_id)
+            .map(|tier| tier.effective_weight(position.base_token_amount))
+            .ok_or_else(|| StdError::generic_err(format!("lockup tier {tier_id} not found"))),
+    }
+}
+
+/// Splits `amount` base tokens off `position` into a new position with ID
+/// `new_id` and the same owner, `release_at` and `created_at`. The vault
+/// tokens of `position` are divided pro rata. Returns the remainder of
+/// `position` and the new position. Returns an error if `amount` is zero or not
+/// less than the amount of `position`.
+pub fn split_unlocking_position(
+    position: &UnlockingPosition,
+    amount: Uint128,
+    new_id: u64,
+) -> StdResult<(UnlockingPosition, UnlockingPosition)> {
+    if amount.is_zero() || amount >= position.base_token_amount {
+        return Err(StdError::generic_err(format!(
+            "split amount must be between zero and {}, exclusive",
+            position.base_token_amount
+        )));
+    }
+
+    let split_vault_tokens = position
+        .vault_token_amount
+        .multiply_ratio(amount, position.base_token_amount);
+    let remainder = UnlockingPosition {
+        base_token_amount: position.base_token_amount - amount,
+        vault_token_amount: position.vault_token_amount - split_vault_tokens,
+        ..position.clone()
+    };
+    let new_position = UnlockingPosition {
+        id: new_id,
+        base_token_amount: amount,
+        vault_token_amount: split_vault_tokens,
+        ..position.clone()
+    };
+
+    Ok((remainder, new_position))
+}
+
+/// Merges `positions` into the first of them, summing the amounts and taking
+/// the latest `release_at`. The merged position keeps the ID and `created_at`
+/// of the first position. Returns an error if fewer than two positions are
+/// given, if a position is given more than once, if they have different owners
+/// or lockup tiers, or if their `release_at` can not be compared because they
+/// use different units.
+pub fn merge_unlocking_positions(positions: &[UnlockingPosition]) -> StdResult<UnlockingPosition> {
+    let (first, rest) = match positions {
+        [first, rest @ ..] if !rest.is_empty() => (first, rest),
+        _ => {
+            return Err(StdError::generic_err(
+                "at least two unlocking positions are required to merge",
+            ))
+        }
+    };
+
+    let mut merged = first.clone();
+    for (i, position) in rest.iter().enumerate() {
+        if position.id == first.id || rest[..i].iter().any(|p| p.id == position.id) {
+            return Err(StdError::generic_err(format!(
+                "unlocking position {} can not be merged more than once",
+                position.id
+            )));
+        }
+        if position.owner != merged.owner {
+            return Err(StdError::generic_err(
+                "can not merge unlocking positions with different owners",
+            ));
+        }
+        if position.tier != merged.tier {
+            return Err(StdError::generic_err(
+                "can not merge unlocking positions in different lockup tiers",
+            ));
+        }
+        merged.base_token_amount = merged
+            .base_token_amount
+            .checked_add(position.base_token_amount)?;
+        merged.vault_token_amount = merged
+            .vault_token_amount
+            .checked_add(position.vault_token_amount)?;
+        merged.release_at = match merged.release_at.partial_cmp(&position.release_at) {
+            Some(std::cmp::Ordering::Less) => position.release_at,
+            Some(_) => merged.release_at,
+            None => {
+                return Err(StdError::generic_err(
+                    "can not merge unlocking positions with different expiration units",
+                ))
+            }
+        };
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{from_json, to_json_string};
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn step_schedule() -> PenaltySchedule {
+        PenaltySchedule::Step {
+            steps: vec![
+                PenaltyStep {
+                    min_remaining: 0,
+                    penalty: dec("0.05"),
+                },
+                PenaltyStep {
+                    min_remaining: 50,
+                    penalty: dec("0.2"),
+                },
+            ],
+        }
+    }
+
+    fn position(id: u64, owner: &str, release_at: u64, amount: u128) -> UnlockingPosition {
+        UnlockingPosition {
+            id,
+            owner: Addr::unchecked(owner),
+            release_at: Expiration::AtTime(Timestamp::from_seconds(release_at)),
+            base_token_amount: Uint128::new(amount),
+            tier: None,
+            vault_token_amount: Uint128::new(amount * 2),
+            created_at: Timestamp::from_seconds(id),
+        }
+    }
+
+    #[test]
+    fn split_divides_amounts_pro_rata() {
+        let (remainder, new) =
+            split_unlocking_position(&position(1, "a", 100, 100), 30u128.into(), 2).unwrap();
+        assert_eq!(remainder.id, 1);
+        assert_eq!(remainder.base_token_amount, Uint128::new(70));
+        assert_eq!(remainder.vault_token_amount, Uint128::new(140));
+        assert_eq!(new.id, 2);
+        assert_eq!(new.base_token_amount, Uint128::new(30));
+        assert_eq!(new.vault_token_amount, Uint128::new(60));
+        assert_eq!(new.release_at, remainder.release_at);
+        assert_eq!(new.created_at, remainder.created_at);
+    }
+
+    #[test]
+    fn split_rejects_zero_and_full_amounts() {
+        let position = position(1, "a", 100, 100);
+        split_unlocking_position(&position, Uint128::zero(), 2).unwrap_err();
+        split_unlocking_position(&position, 100u128.into(), 2).unwrap_err();
+    }
+
+    #[test]
+    fn merge_sums_amounts_and_takes_latest_release() {
+        let merged = merge_unlocking_positions(&[
+            position(1, "a", 100, 10),
+            position(2, "a", 300, 20),
+            position(3, "a", 200, 30),
+        ])
+        .unwrap();
+        assert_eq!(merged.id, 1);
+        assert_eq!(merged.created_at, Timestamp::from_seconds(1));
+        assert_eq!(merged.base_token_amount, Uint128::new(60));
+        assert_eq!(merged.vault_token_amount, Uint128::new(120));
+        assert_eq!(
+            merged.release_at,
+            Expiration::AtTime(Timestamp::from_seconds(300))
+        );
+    }
+
+    #[test]
+    fn merge_rejects_duplicate_positions() {
+        let err =
+            merge_unlocking_positions(&[position(1, "a", 100, 10), position(1, "a", 100, 10)])
+                .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("unlocking position 1 can not be merged more than once")
+        );
+        merge_unlocking_positions(&[
+            position(1, "a", 100, 10),
+            position(2, "a", 100, 10),
+            position(2, "a", 100, 10),
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn merge_rejects_incompatible_positions() {
+        merge_unlocking_positions(&[position(1, "a", 100, 10)]).unwrap_err();
+        merge_unlocking_positions(&[position(1, "a", 100, 10), position(2, "b", 100, 10)])
+            .unwrap_err();
+
+        let mut tiered = position(2, "a", 100, 10);
+        tiered.tier = Some(1);
+        merge_unlocking_positions(&[position(1, "a", 100, 10), tiered]).unwrap_err();
+
+        let mut at_height = position(2, "a", 100, 10);
+        at_height.release_at = Expiration::AtHeight(100);
+        merge_unlocking_positions(&[position(1, "a", 100, 10), at_height]).unwrap_err();
+    }
+
+    #[test]
+    fn linear_penalty_decreases_with_remaining_lockup() {
+        let schedule = PenaltySchedule::Linear {
+            max_penalty: dec("0.5"),
+        };
+        assert_eq!(schedule.penalty_rate(100, 100), dec("0.5"));
+        assert_eq!(schedule.penalty_rate(200, 100), dec("0.5"));
+        assert_eq!(schedule.penalty_rate(25, 100), dec("0.125"));
+        assert_eq!(schedule.penalty_rate(0, 100), Decimal::zero());
+        assert_eq!(schedule.penalty_rate(10, 0), Decimal::zero());
+    }
+
+    #[test]
+    fn linear_penalty_is_capped_at_one() {
+        let schedule = PenaltySchedule::Linear {
+            max_penalty: dec("3"),
+        };
+        assert_eq!(schedule.penalty_rate(100, 100), Decimal::one());
+    }
+
+    #[test]
+    fn step_penalty_uses_largest_applicable_step() {
+        let schedule = step_schedule();
+        assert_eq!(schedule.penalty_rate(100, 100), dec("0.2"));
+        assert_eq!(schedule.penalty_rate(50, 100), dec("0.2"));
+        assert_eq!(schedule.penalty_rate(49, 100), dec("0.05"));
+        assert_eq!(schedule.penalty_rate(1, 100), dec("0.05"));
+    }
+
+    #[test]
+    fn no_penalty_once_nothing_remains() {
+        assert_eq!(step_schedule().penalty_rate(0, 100), Decimal::zero());
+    }
+
+    #[test]
+    fn preview_early_withdraw_of_unlocking_position() {
+        let env = mock_env();
+        let now = env.block.time.seconds();
+        let preview = preview_early_withdraw(
+            &position(1, "a", now + 50, 1000),
+            &Duration::Time(100),
+            &step_schedule(),
+            &env.block,
+        )
+        .unwrap();
+        assert_eq!(
+            preview,
+            EarlyWithdrawPreview {
+                net_amount: Uint128::new(800),
+                penalty: Uint128::new(200),
+            }
+        );
+    }
+
+    #[test]
+    fn preview_early_withdraw_of_matured_position_has_no_penalty() {
+        let env = mock_env();
+        let now = env.block.time.seconds();
+        for release_at in [now - 10, now] {
+            let preview = preview_early_withdraw(
+                &position(1, "a", release_at, 1000),
+                &Duration::Time(100),
+                &step_schedule(),
+                &env.block,
+            )
+            .unwrap();
+            assert_eq!(
+                preview,
+                EarlyWithdrawPreview {
+                    net_amount: Uint128::new(1000),
+                    penalty: Uint128::zero(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn preview_early_withdraw_rejects_mismatched_units() {
+        let env = mock_env();
+        preview_early_withdraw(
+            &position(1, "a", env.block.time.seconds() + 50, 1000),
+            &Duration::Height(100),
+            &step_schedule(),
+            &env.block,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn unlock_without_tier_is_serialized_as_before() {
+        let unlock = LockupExecuteMsg::Unlock {
+            amount: Uint128::one(),
+            tier: None,
+        };
+        let json = to_json_string(&unlock).unwrap();
+        assert_eq!(json, r#"{"unlock":{"amount":"1"}}"#);
+        assert_eq!(from_json::<LockupExecuteMsg>(&json).unwrap(), unlock);
+
+        let unlock = LockupExecuteMsg::Unlock {
+            amount: Uint128::one(),
+            tier: Some(2),
+        };
+        let json = to_json_string(&unlock).unwrap();
+        assert_eq!(json, r#"{"unlock":{"amount":"1","tier":2}}"#);
+        assert_eq!(from_json::<LockupExecuteMsg>(&json).unwrap(), unlock);
+    }
 }
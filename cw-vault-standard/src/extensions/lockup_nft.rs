@@ -0,0 +1,580 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, Coin, CosmosMsg, Empty, MessageInfo, QuerierWrapper,
+    StdError, StdResult, Uint128, WasmMsg,
+};
+use cw_utils::Expiration;
+use serde::Serialize;
+
+use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
+
+/// Returns the cw721 token ID of the unlocking position with ID `lockup_id`,
+/// which is the lockup ID as a decimal string zero-padded to 20 digits, so
+/// that cw721 contracts, which order tokens by their string token ID, return
+/// the positions in the order of their lockup IDs.
+pub fn lockup_id_to_token_id(lockup_id: u64) -> String {
+    format!("{lockup_id:020}")
+}
+
+/// Returns the lockup ID of the unlocking position represented by the cw721
+/// token with ID `token_id`. Returns an error unless `token_id` was created by
+/// [`lockup_id_to_token_id`].
+pub fn token_id_to_lockup_id(token_id: &str) -> StdResult<u64> {
+    token_id
+        .parse()
+        .ok()
+        .filter(|lockup_id| lockup_id_to_token_id(*lockup_id) == token_id)
+        .ok_or_else(|| StdError::generic_err(format!("invalid lockup token id: {token_id}")))
+}
+
+/// The top level ExecuteMsg of a vault that enables the LockupNft extension.
+/// This includes all of the variants from the default VaultStandardExecuteMsg,
+/// plus the `ReceiveNft` variant that the cw721 contract calls when the token
+/// of an unlocking position is sent to the vault. This enum can be extended
+/// with additional variants by defining an extension enum and then passing it
+/// as the generic argument `T` to this enum.
+#[cw_serde]
+pub enum LockupNftExecuteMsg<T = ExtensionExecuteMsg> {
+    /// Called by the cw721 contract when the token of an unlocking position is
+    /// sent to the vault with `SendNft`. The attached message is a
+    /// [`LockupNftReceiveMsg`].
+    ReceiveNft(Cw721ReceiveMsg),
+
+    /// Called to deposit into the vault. Native assets are passed in the funds
+    /// parameter.
+    Deposit {
+        /// The amount of base tokens to deposit.
+        #[deprecated(
+            since = "0.4.1",
+            note = "This field will be removed in the next version. The amount \
+            of deposited assets should instead be read from the actual sent funds."
+        )]
+        amount: Uint128,
+        /// The optional recipient of the vault token. If not set, the caller
+        /// address will be used instead.
+        recipient: Option<String>,
+    },
+
+    /// Called to redeem vault tokens and receive assets back from the vault.
+    /// The native vault token must be passed in the funds parameter, unless the
+    /// lockup extension is called, in which case the vault token has already
+    /// been passed to ExecuteMsg::Unlock.
+    Redeem {
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+        /// The amount of vault tokens sent to the contract.
+        #[deprecated(
+            since = "0.4.1",
+            note = "This field will be removed in the next version. The amount \
+            of vault tokens should instead be read from the actual amount of sent vault tokens."
+        )]
+        amount: Uint128,
+    },
+
+    /// Called to execute functionality of any enabled extensions.
+    VaultExtension(T),
+}
+
+impl<T> LockupNftExecuteMsg<T>
+where
+    T: Serialize,
+{
+    /// Convert a [`LockupNftExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&self)?,
+            funds,
+        }
+        .into())
+    }
+}
+
+#[allow(deprecated)]
+impl<T> From<VaultStandardExecuteMsg<T>> for LockupNftExecuteMsg<T> {
+    fn from(msg: VaultStandardExecuteMsg<T>) -> Self {
+        match msg {
+            VaultStandardExecuteMsg::Deposit { amount, recipient } => {
+                LockupNftExecuteMsg::Deposit { amount, recipient }
+            }
+            VaultStandardExecuteMsg::Redeem { recipient, amount } => {
+                LockupNftExecuteMsg::Redeem { recipient, amount }
+            }
+            VaultStandardExecuteMsg::VaultExtension(msg) => {
+                LockupNftExecuteMsg::VaultExtension(msg)
+            }
+        }
+    }
+}
+
+/// Converts every variant except `ReceiveNft`, which has no counterpart in
+/// [`VaultStandardExecuteMsg`] and must be handled by the vault itself, e.g.
+/// with [`receive_nft`](crate::framework::receive_nft).
+#[allow(deprecated)]
+impl<T> TryFrom<LockupNftExecuteMsg<T>> for VaultStandardExecuteMsg<T> {
+    type Error = StdError;
+
+    fn try_from(msg: LockupNftExecuteMsg<T>) -> StdResult<Self> {
+        match msg {
+            LockupNftExecuteMsg::ReceiveNft(_) => Err(StdError::generic_err(
+                "ReceiveNft can not be converted into a VaultStandardExecuteMsg",
+            )),
+            LockupNftExecuteMsg::Deposit { amount, recipient } => {
+                Ok(VaultStandardExecuteMsg::Deposit { amount, recipient })
+            }
+            LockupNftExecuteMsg::Redeem { recipient, amount } => {
+                Ok(VaultStandardExecuteMsg::Redeem { recipient, amount })
+            }
+            LockupNftExecuteMsg::VaultExtension(msg) => {
+                Ok(VaultStandardExecuteMsg::VaultExtension(msg))
+            }
+        }
+    }
+}
+
+/// Additional QueryMsg variants for vaults that enable the LockupNft
+/// extension.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum LockupNftQueryMsg {
+    /// Returns the `Addr` of the cw721 contract in which the unlocking
+    /// positions of the vault are minted.
+    #[returns(Addr)]
+    NftContract {},
+}
+
+/// The subset of the cw721 `ExecuteMsg` used by vaults that enable the
+/// LockupNft extension.
+#[cw_serde]
+pub enum Cw721ExecuteMsg {
+    /// Mint a new NFT. Can only be called by the minter of the cw721 contract,
+    /// which should be the vault.
+    Mint {
+        /// Unique ID of the NFT.
+        token_id: String,
+        /// The owner of the newly minted NFT.
+        owner: String,
+        /// Universal resource identifier for this NFT.
+        token_uri: Option<String>,
+        /// Any custom extension used by this contract.
+        extension: Empty,
+    },
+    /// Burn an NFT the sender has access to, i.e. that the sender owns or is
+    /// approved to transfer.
+    Burn {
+        /// The ID of the NFT to burn.
+        token_id: String,
+    },
+}
+
+/// The subset of the cw721 `QueryMsg` used by vaults that enable the LockupNft
+/// extension.
+#[cw_serde]
+pub enum Cw721QueryMsg {
+    /// Returns the owner of the given token, as an `OwnerOfResponse`.
+    OwnerOf {
+        /// The ID of the NFT.
+        token_id: String,
+        /// Unset or false will filter out expired approvals.
+        include_expired: Option<bool>,
+    },
+    /// Returns the approval of `spender` for the given token, as an
+    /// `ApprovalResponse`. Returns an error if `spender` is not approved.
+    Approval {
+        /// The ID of the NFT.
+        token_id: String,
+        /// The address of the spender.
+        spender: String,
+        /// Unset or false will filter out expired approvals.
+        include_expired: Option<bool>,
+    },
+    /// Returns the IDs of the tokens owned by `owner`, as a `TokensResponse`.
+    Tokens {
+        /// The address of the owner.
+        owner: String,
+        /// Return results only after this token ID.
+        start_after: Option<String>,
+        /// Max amount of results to return.
+        limit: Option<u32>,
+    },
+}
+
+/// An approval of a spender to transfer a cw721 token.
+#[cw_serde]
+pub struct Approval {
+    /// The account that can transfer the token.
+    pub spender: String,
+    /// When the approval expires.
+    pub expires: Expiration,
+}
+
+/// Response of [`Cw721QueryMsg::OwnerOf`].
+#[cw_serde]
+pub struct OwnerOfResponse {
+    /// The owner of the token.
+    pub owner: String,
+    /// The approvals of the token.
+    pub approvals: Vec<Approval>,
+}
+
+/// Response of [`Cw721QueryMsg::Approval`].
+#[cw_serde]
+pub struct ApprovalResponse {
+    /// The approval of the spender.
+    pub approval: Approval,
+}
+
+/// Response of [`Cw721QueryMsg::Tokens`].
+#[cw_serde]
+pub struct TokensResponse {
+    /// The IDs of the tokens, ordered by token ID.
+    pub tokens: Vec<String>,
+}
+
+/// The message sent by a cw721 contract to the recipient of `SendNft`. Vaults
+/// that enable the LockupNft extension receive it through the `ReceiveNft`
+/// variant of [`LockupNftExecuteMsg`].
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    /// The previous owner of the NFT.
+    pub sender: String,
+    /// The ID of the NFT.
+    pub token_id: String,
+    /// The message attached to `SendNft`, a [`LockupNftReceiveMsg`].
+    pub msg: Binary,
+}
+
+/// The messages that can be attached to `SendNft` when sending the token of an
+/// unlocking position to the vault.
+#[cw_serde]
+pub enum LockupNftReceiveMsg {
+    /// Withdraw the base tokens of the unlocked position represented by the
+    /// sent token. The token is burned.
+    WithdrawUnlocked {
+        /// The address that should receive the base tokens. If not set, the
+        /// previous owner of the token will be used.
+        recipient: Option<String>,
+    },
+}
+
+/// Helper for interacting with the cw721 contract in which the unlocking
+/// positions of a vault are minted.
+///
+/// The vault should mint a token with [`LockupNft::mint`] when an unlocking
+/// position is created in `Unlock`, and burn it with [`LockupNft::burn`] when
+/// the position is withdrawn. In between, the owner of the token in the cw721
+/// contract is the owner of the unlocking position. It is authoritative: the
+/// vault must not track owners of its own, the `UnlockingPosition` and
+/// `UnlockingPositions` queries must resolve the owner with
+/// [`LockupNft::query_owner`] and [`LockupNft::query_lockup_ids`], and
+/// positions are transferred by transferring their token, so vaults must reject
+/// `TransferUnlockingPosition`. Positions created by `SplitUnlockingPosition`
+/// must be minted, and positions removed by `MergeUnlockingPositions` burned.
+/// The [`execute_lockup_nft`](crate::framework::execute_lockup_nft) dispatcher
+/// enforces this.
+///
+/// Since the vault can only burn tokens it owns or is approved for, owners
+/// withdraw their unlocked positions in one of two ways:
+/// * By sending the token to the vault with the cw721 `SendNft` message and a
+///   [`LockupNftReceiveMsg::WithdrawUnlocked`] message, which the vault handles
+///   with [`LockupNft::receive`]. This is the recommended way, as it needs a
+///   single transaction.
+/// * By approving the vault for the token with the cw721 `Approve` message and
+///   calling `WithdrawUnlocked`, which the vault must authorize with
+///   [`LockupNft::assert_owner`] and [`LockupNft::assert_approved`].
+#[cw_serde]
+pub struct LockupNft(pub Addr);
+
+impl LockupNft {
+    /// Create a new LockupNft for the cw721 contract at `address`.
+    pub fn new(address: Addr) -> Self {
+        Self(address)
+    }
+
+    /// Returns the address of the cw721 contract.
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    fn execute(&self, msg: &Cw721ExecuteMsg) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_json_binary(msg)?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    /// Returns a message that mints the token of the unlocking position with ID
+    /// `lockup_id` to `owner`.
+    pub fn mint(&self, lockup_id: u64, owner: &Addr) -> StdResult<CosmosMsg> {
+        self.execute(&Cw721ExecuteMsg::Mint {
+            token_id: lockup_id_to_token_id(lockup_id),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Empty {},
+        })
+    }
+
+    /// Returns a message that burns the token of the unlocking position with ID
+    /// `lockup_id`. The message fails unless the vault owns the token, e.g.
+    /// after receiving it with `SendNft`, or is approved for it.
+    pub fn burn(&self, lockup_id: u64) -> StdResult<CosmosMsg> {
+        self.execute(&Cw721ExecuteMsg::Burn {
+            token_id: lockup_id_to_token_id(lockup_id),
+        })
+    }
+
+    /// Returns the current owner of the unlocking position with ID
+    /// `lockup_id`.
+    pub fn query_owner(&self, querier: &QuerierWrapper, lockup_id: u64) -> StdResult<Addr> {
+        let res: OwnerOfResponse = querier.query_wasm_smart(
+            &self.0,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: lockup_id_to_token_id(lockup_id),
+                include_expired: None,
+            },
+        )?;
+        Ok(Addr::unchecked(res.owner))
+    }
+
+    /// Returns the IDs of the unlocking positions currently owned by `owner`,
+    /// in ascending order.
+    pub fn query_lockup_ids(
+        &self,
+        querier: &QuerierWrapper,
+        owner: &Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<u64>> {
+        let res: TokensResponse = querier.query_wasm_smart(
+            &self.0,
+            &Cw721QueryMsg::Tokens {
+                owner: owner.to_string(),
+                start_after: start_after.map(lockup_id_to_token_id),
+                limit,
+            },
+        )?;
+        res.tokens
+            .iter()
+            .map(|token_id| token_id_to_lockup_id(token_id))
+            .collect()
+    }
+
+    /// Returns an error unless the sender of the message currently owns the
+    /// unlocking position with ID `lockup_id`.
+    pub fn assert_owner(
+        &self,
+        querier: &QuerierWrapper,
+        info: &MessageInfo,
+        lockup_id: u64,
+    ) -> StdResult<()> {
+        if self.query_owner(querier, lockup_id)? != info.sender {
+            return Err(StdError::generic_err(format!(
+                "{} does not own unlocking position {lockup_id}",
+                info.sender
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns an error unless `spender`, usually the vault, is approved to
+    /// transfer and burn the token of the unlocking position with ID
+    /// `lockup_id`. Errors of the cw721 contract other than a missing approval,
+    /// e.g. because the contract does not exist, are returned as they are.
+    pub fn assert_approved(
+        &self,
+        querier: &QuerierWrapper,
+        spender: &Addr,
+        lockup_id: u64,
+    ) -> StdResult<()> {
+        let res = querier.query_wasm_smart::<ApprovalResponse>(
+            &self.0,
+            &Cw721QueryMsg::Approval {
+                token_id: lockup_id_to_token_id(lockup_id),
+                spender: spender.to_string(),
+                include_expired: None,
+            },
+        );
+        match res {
+            Ok(_) => Ok(()),
+            // cw721-base returns a `NotFound` error for missing approvals, which
+            // reaches the vault as a generic querier error.
+            Err(StdError::NotFound { .. }) => Err(not_approved(spender, lockup_id)),
+            Err(StdError::GenericErr { msg, .. }) if msg.contains("not found") => {
+                Err(not_approved(spender, lockup_id))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Handles a `ReceiveNft` message sent by the cw721 contract when the
+    /// token of an unlocking position is sent to the vault. Returns the lockup
+    /// ID of the position, the previous owner of the token and the attached
+    /// message. Returns an error if the message was not sent by the cw721
+    /// contract.
+    ///
+    /// After this the vault owns the token, so it can be burned with
+    /// [`LockupNft::burn`] when the position is withdrawn.
+    pub fn receive(
+        &self,
+        info: &MessageInfo,
+        msg: Cw721ReceiveMsg,
+    ) -> StdResult<(u64, Addr, LockupNftReceiveMsg)> {
+        if info.sender != self.0 {
+            return Err(StdError::generic_err(format!(
+                "unauthorized: only {} can send unlocking positions",
+                self.0
+            )));
+        }
+        Ok((
+            token_id_to_lockup_id(&msg.token_id)?,
+            Addr::unchecked(msg.sender),
+            from_json(&msg.msg)?,
+        ))
+    }
+}
+
+fn not_approved(spender: &Addr, lockup_id: u64) -> StdError {
+    StdError::generic_err(format!(
+        "{spender} is not approved for unlocking position {lockup_id}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+    use cosmwasm_std::{to_json_string, ContractResult, SystemError, SystemResult, WasmQuery};
+
+    #[test]
+    fn token_ids_round_trip() {
+        for lockup_id in [0, 1, 42, u64::MAX] {
+            let token_id = lockup_id_to_token_id(lockup_id);
+            assert_eq!(token_id.len(), 20);
+            assert_eq!(token_id_to_lockup_id(&token_id).unwrap(), lockup_id);
+        }
+        assert_eq!(lockup_id_to_token_id(42), "00000000000000000042");
+    }
+
+    #[test]
+    fn token_ids_sort_by_lockup_id() {
+        let lockup_ids = [0, 2, 9, 10, 11, 100, 1_000_000, u64::MAX];
+        let mut token_ids: Vec<_> = lockup_ids
+            .iter()
+            .rev()
+            .map(|id| lockup_id_to_token_id(*id))
+            .collect();
+        token_ids.sort();
+        let sorted: Vec<_> = token_ids
+            .iter()
+            .map(|token_id| token_id_to_lockup_id(token_id).unwrap())
+            .collect();
+        assert_eq!(sorted, lockup_ids);
+    }
+
+    #[test]
+    fn non_canonical_token_ids_are_rejected() {
+        for token_id in [
+            "",
+            "1",
+            "42",
+            "-0000000000000000001",
+            "0000000000000000004a",
+        ] {
+            token_id_to_lockup_id(token_id).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn receive_only_accepts_tokens_from_nft_contract() {
+        let nft = LockupNft::new(Addr::unchecked("nft"));
+        let msg = Cw721ReceiveMsg {
+            sender: "owner".to_string(),
+            token_id: lockup_id_to_token_id(7),
+            msg: to_json_binary(&LockupNftReceiveMsg::WithdrawUnlocked { recipient: None })
+                .unwrap(),
+        };
+
+        nft.receive(&mock_info("other", &[]), msg.clone())
+            .unwrap_err();
+
+        let (lockup_id, owner, receive_msg) = nft.receive(&mock_info("nft", &[]), msg).unwrap();
+        assert_eq!(lockup_id, 7);
+        assert_eq!(owner, Addr::unchecked("owner"));
+        assert_eq!(
+            receive_msg,
+            LockupNftReceiveMsg::WithdrawUnlocked { recipient: None }
+        );
+    }
+
+    #[test]
+    fn execute_msg_wraps_vault_standard_messages() {
+        let msg = LockupNftExecuteMsg::<Empty>::ReceiveNft(Cw721ReceiveMsg {
+            sender: "owner".to_string(),
+            token_id: lockup_id_to_token_id(7),
+            msg: Binary::default(),
+        });
+        assert_eq!(
+            to_json_string(&msg).unwrap(),
+            r#"{"receive_nft":{"sender":"owner","token_id":"00000000000000000007","msg":""}}"#
+        );
+        VaultStandardExecuteMsg::try_from(msg).unwrap_err();
+
+        #[allow(deprecated)]
+        let deposit = VaultStandardExecuteMsg::<Empty>::Deposit {
+            amount: Uint128::new(1),
+            recipient: None,
+        };
+        let msg = LockupNftExecuteMsg::from(deposit.clone());
+        assert_eq!(
+            to_json_string(&msg).unwrap(),
+            to_json_string(&deposit).unwrap()
+        );
+        assert_eq!(VaultStandardExecuteMsg::try_from(msg).unwrap(), deposit);
+    }
+
+    #[test]
+    fn assert_approved_propagates_querier_errors() {
+        let nft = LockupNft::new(Addr::unchecked("nft"));
+        let vault = Addr::unchecked("vault");
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "nft" => {
+                let res = match from_json(msg).unwrap() {
+                    Cw721QueryMsg::Approval { token_id, .. }
+                        if token_id == lockup_id_to_token_id(1) =>
+                    {
+                        to_json_binary(&ApprovalResponse {
+                            approval: Approval {
+                                spender: "vault".to_string(),
+                                expires: Expiration::Never {},
+                            },
+                        })
+                        .into()
+                    }
+                    _ => ContractResult::Err(StdError::not_found("Approval").to_string()),
+                };
+                SystemResult::Ok(res)
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: "other".to_string(),
+            }),
+        });
+        let querier = deps.as_ref().querier;
+
+        nft.assert_approved(&querier, &vault, 1).unwrap();
+        assert_eq!(
+            nft.assert_approved(&querier, &vault, 2).unwrap_err(),
+            StdError::generic_err("vault is not approved for unlocking position 2")
+        );
+
+        let err = LockupNft::new(Addr::unchecked("other"))
+            .assert_approved(&querier, &vault, 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("No such contract"), "{err}");
+    }
+}
@@ -1,3 +1,31 @@
+/// Canonical name of the [Lockup](crate::extensions::lockup) extension, as
+/// used in [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const LOCKUP_EXTENSION_NAME: &str = "lockup";
+/// Canonical name of the [LockupNft](crate::extensions::lockup_nft)
+/// extension, as used in
+/// [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const LOCKUP_NFT_EXTENSION_NAME: &str = "lockup-nft";
+/// Canonical name of the [ForceUnlock](crate::extensions::force_unlock)
+/// extension, as used in
+/// [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const FORCE_UNLOCK_EXTENSION_NAME: &str = "force-unlock";
+/// Canonical name of the [Keeper](crate::extensions::keeper) extension, as
+/// used in [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const KEEPER_EXTENSION_NAME: &str = "keeper";
+/// Canonical name of the [Strategies](crate::extensions::strategies)
+/// extension, as used in
+/// [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const STRATEGIES_EXTENSION_NAME: &str = "strategies";
+/// Canonical name of the [Harvest](crate::extensions::harvest) extension, as
+/// used in [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const HARVEST_EXTENSION_NAME: &str = "harvest";
+/// Canonical name of the [Analytics](crate::extensions::analytics) extension,
+/// as used in [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const ANALYTICS_EXTENSION_NAME: &str = "analytics";
+/// Canonical name of the [Cw4626](crate::extensions::cw4626) extension, as
+/// used in [`ExtensionInfo::name`](crate::ExtensionInfo::name).
+pub const CW4626_EXTENSION_NAME: &str = "cw4626";
+
 /// The lockup extension can be used to create vaults where the vault tokens are
 /// not immediately reedemable. Instead of normally calling the
 /// `VaultStandardExecuteMsg::Redeem` variant, the user has to call the `Unlock`
@@ -8,6 +36,16 @@
 #[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
 pub mod lockup;
 
+/// The lockup NFT extension can be used together with the `Lockup` extension to
+/// make unlocking positions transferable. Each unlocking position is minted as
+/// a cw721 token, and whoever owns the token owns the position: only they can
+/// withdraw it with `WithdrawUnlocked`, and the lockup queries resolve the
+/// owner of a position through the cw721 contract. This allows unlocking
+/// positions to be sold or used as collateral.
+#[cfg(feature = "lockup-nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup-nft")))]
+pub mod lockup_nft;
+
 /// The force unlock extension can be used to create a vault that also
 /// implements the `Lockup` extension, but where some whitelisted addresses are
 /// allowed to call the `ForceUnlock` variant on the extension `ExecuteMsg` and
@@ -25,6 +63,31 @@ pub mod force_unlock;
 #[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
 pub mod keeper;
 
+/// The strategies extension can be used to create vaults that allocate their
+/// base tokens to one or more strategies. Each strategy is represented by an
+/// adapter contract and has a debt limit which caps the amount of base tokens
+/// that can be allocated to it. The vault admin manages the strategies, while
+/// keepers move funds between the vault and the strategies.
+#[cfg(feature = "strategies")]
+#[cfg_attr(docsrs, doc(cfg(feature = "strategies")))]
+pub mod strategies;
+
+/// The harvest extension can be used to create auto-compounding vaults that
+/// report the result of each harvest. Every call to `Harvest` persists a
+/// `HarvestReport` with the gain, loss and fees of the harvest, which can then
+/// be queried together with the rewards that are still pending.
+#[cfg(feature = "harvest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "harvest")))]
+pub mod harvest;
+
+/// The analytics extension can be used to create vaults that keep a history of
+/// their exchange rate. The vault periodically snapshots its exchange rate and
+/// total assets into a ring buffer, which allows the realized APR of the vault
+/// to be computed on-chain.
+#[cfg(feature = "analytics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analytics")))]
+pub mod analytics;
+
 /// The Cw4626 extension is the only extension provided with in this repo that
 /// does not extend the standard `ExecuteMsg` and `QueryMsg` enums with by
 /// putting its variants inside of a `VaultExtension` variant. Instead it adds
@@ -0,0 +1,166 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, StdResult, Uint128, WasmMsg};
+
+use crate::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
+
+/// Type for the event emitted on call to `Allocate`.
+pub const STRATEGY_ALLOCATE_EVENT_TYPE: &str = "strategy_allocate";
+/// Type for the event emitted on call to `Deallocate`.
+pub const STRATEGY_DEALLOCATE_EVENT_TYPE: &str = "strategy_deallocate";
+/// Key for the strategy id attribute in the events emitted on calls to
+/// `AddStrategy`, `Allocate` and `Deallocate`.
+pub const STRATEGY_ID_ATTR_KEY: &str = "strategy_id";
+
+/// A strategy that the vault can allocate base tokens to.
+#[cw_serde]
+pub struct Strategy {
+    /// The numeric ID of the strategy.
+    pub id: u64,
+    /// The address of the adapter contract that implements the
+    /// [`StrategyAdapterExecuteMsg`] and [`StrategyAdapterQueryMsg`]
+    /// interface for this strategy.
+    pub adapter: Addr,
+    /// The maximum amount of base tokens that the vault may allocate to the
+    /// strategy.
+    pub debt_limit: Uint128,
+    /// The amount of base tokens currently allocated to the strategy.
+    pub current_debt: Uint128,
+}
+
+/// Additional ExecuteMsg variants for vaults that enable the Strategies
+/// extension.
+#[cw_serde]
+pub enum StrategiesExecuteMsg {
+    /// Callable by vault admin to add a new strategy to the vault. The new
+    /// strategy starts out with a `current_debt` of zero.
+    /// Emits an event with an attribute with key `STRATEGY_ID_ATTR_KEY`
+    /// containing the u64 ID of the new strategy.
+    AddStrategy {
+        /// The address of the adapter contract of the strategy.
+        adapter: String,
+        /// The maximum amount of base tokens that may be allocated to the
+        /// strategy.
+        debt_limit: Uint128,
+    },
+
+    /// Callable by vault admin to remove a strategy from the vault. Should
+    /// fail if the strategy still has a non-zero `current_debt`.
+    RemoveStrategy {
+        /// The ID of the strategy to remove.
+        strategy_id: u64,
+    },
+
+    /// Callable by vault admin to update the debt limit of a strategy. Lowering
+    /// the debt limit below the `current_debt` does not deallocate any funds,
+    /// but prevents further allocations to the strategy.
+    UpdateDebtLimit {
+        /// The ID of the strategy to update.
+        strategy_id: u64,
+        /// The new maximum amount of base tokens that may be allocated to the
+        /// strategy.
+        debt_limit: Uint128,
+    },
+
+    /// Callable by keepers to move idle base tokens from the vault into a
+    /// strategy. Should fail if the `current_debt` of the strategy would
+    /// exceed its `debt_limit`.
+    /// Emits an event with type `STRATEGY_ALLOCATE_EVENT_TYPE`.
+    Allocate {
+        /// The ID of the strategy to allocate to.
+        strategy_id: u64,
+        /// The amount of base tokens to allocate.
+        amount: Uint128,
+    },
+
+    /// Callable by keepers to withdraw base tokens from a strategy back into
+    /// the vault.
+    /// Emits an event with type `STRATEGY_DEALLOCATE_EVENT_TYPE`.
+    Deallocate {
+        /// The ID of the strategy to deallocate from.
+        strategy_id: u64,
+        /// The amount of base tokens to deallocate.
+        amount: Uint128,
+    },
+}
+
+impl StrategiesExecuteMsg {
+    /// Convert a [`StrategiesExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&VaultStandardExecuteMsg::VaultExtension(
+                ExtensionExecuteMsg::Strategies(self),
+            ))?,
+            funds,
+        }
+        .into())
+    }
+}
+
+/// Additional QueryMsg variants for vaults that enable the Strategies
+/// extension.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum StrategiesQueryMsg {
+    /// Returns a `Vec<Strategy>` containing the strategies of the vault,
+    /// ordered by ID.
+    #[returns(Vec<Strategy>)]
+    Strategies {
+        /// Return results only after this strategy ID
+        start_after: Option<u64>,
+        /// Max amount of results to return
+        limit: Option<u32>,
+    },
+
+    /// Returns a `Strategy` with info about a specific strategy, by ID.
+    #[returns(Strategy)]
+    Strategy {
+        /// The ID of the strategy to query
+        strategy_id: u64,
+    },
+}
+
+/// ExecuteMsg variants that a strategy adapter contract must implement to be
+/// used with the Strategies extension. Only the vault that the adapter belongs
+/// to should be allowed to call these.
+#[cw_serde]
+pub enum StrategyAdapterExecuteMsg {
+    /// Deposit base tokens into the underlying strategy. The base tokens are
+    /// passed in the funds field.
+    Deposit {},
+
+    /// Withdraw base tokens from the underlying strategy and send them to the
+    /// caller.
+    Withdraw {
+        /// The amount of base tokens to withdraw.
+        amount: Uint128,
+    },
+}
+
+impl StrategyAdapterExecuteMsg {
+    /// Convert a [`StrategyAdapterExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&self)?,
+            funds,
+        }
+        .into())
+    }
+}
+
+/// QueryMsg variants that a strategy adapter contract must implement to be
+/// used with the Strategies extension.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum StrategyAdapterQueryMsg {
+    /// Returns `Uint128` amount of base tokens held by the strategy on behalf
+    /// of the vault, including any unrealized gains or losses.
+    #[returns(Uint128)]
+    TotalAssets {},
+
+    /// Returns `Uint128` maximum amount of base tokens that can currently be
+    /// withdrawn from the strategy.
+    #[returns(Uint128)]
+    MaxWithdraw {},
+}
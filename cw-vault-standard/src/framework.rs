@@ -0,0 +1,3012 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Response,
+    StdError, StdResult, Uint128,
+};
+use cw_utils::PaymentError;
+use schemars::JsonSchema;
+
+#[cfg(feature = "force-unlock")]
+use crate::extensions::force_unlock::ForceUnlockExecuteMsg;
+#[cfg(feature = "keeper")]
+use crate::extensions::keeper::{
+    KeeperExecuteMsg, KeeperJob, KeeperJobConfig, KeeperJobExecution, KeeperJobStatus,
+    KeeperQueryMsg, JOB_ID_ATTR_KEY, KEEPER_ATTR_KEY, KEEPER_BOUNTY_PAID_EVENT_TYPE,
+    REWARDS_ATTR_KEY,
+};
+#[cfg(feature = "lockup")]
+use crate::extensions::lockup::{
+    preview_early_withdraw, CancelUnlockMode, ClaimableUnlockedResponse, EarlyWithdrawConfig,
+    EarlyWithdrawPreview, LockupExecuteMsg, LockupQueryMsg, LockupTier, UnlockingPosition,
+};
+#[cfg(feature = "lockup-nft")]
+use crate::extensions::lockup_nft::{
+    Cw721ReceiveMsg, LockupNft, LockupNftQueryMsg, LockupNftReceiveMsg,
+};
+#[cfg(feature = "keeper")]
+use cosmwasm_std::Coin;
+#[cfg(feature = "lockup")]
+use cosmwasm_std::Timestamp;
+#[cfg(feature = "lockup")]
+use cw_utils::Duration;
+
+#[cfg(any(feature = "lockup", feature = "force-unlock", feature = "keeper"))]
+use crate::validation::nonpayable;
+use crate::validation::{must_pay_base_tokens, must_pay_vault_tokens, resolve_recipient};
+use crate::{
+    ExtensionInfo, UserPositionResponse, VaultInfoResponse, VaultStandardExecuteMsg,
+    VaultStandardInfoResponse, VaultStandardQueryMsg, VaultStateResponse, VERSION,
+};
+
+/// Type for the event emitted by [`execute`] on call to `Deposit`.
+pub const DEPOSIT_EVENT_TYPE: &str = "vault_deposit";
+/// Type for the event emitted by [`execute`] on call to `Redeem`.
+pub const REDEEM_EVENT_TYPE: &str = "vault_redeem";
+/// Key for the attribute containing the amount of sent tokens in the events
+/// emitted by [`execute`].
+pub const AMOUNT_ATTR_KEY: &str = "amount";
+/// Key for the attribute containing the recipient in the events emitted by
+/// [`execute`].
+pub const RECIPIENT_ATTR_KEY: &str = "recipient";
+
+/// The core functionality of a vault. Implementing this trait and the
+/// [`ExtensionExecuteImpl`] and [`ExtensionQueryImpl`] traits for the
+/// extension enums of the vault allows the [`execute`] and [`query`] functions
+/// to be used as the entrypoints of the vault contract.
+///
+/// The share math of the provided methods is based on `total_assets` and
+/// `total_vault_token_supply`, and can be overridden if the vault uses
+/// different accounting.
+pub trait VaultImpl {
+    /// The error type returned by the execute methods of the vault.
+    type Error: From<StdError> + From<PaymentError>;
+
+    /// Returns the base token and vault token of the vault.
+    fn vault_info(&self, deps: Deps, env: Env) -> StdResult<VaultInfoResponse>;
+
+    /// Returns the version of the vault standard used by the vault, which is
+    /// returned in the `VaultStandardInfo` and `VaultState` queries. Defaults
+    /// to [`VERSION`]. Vaults whose entrypoints accept the messages of the
+    /// [`v0_5`](crate::v0_5) module should return
+    /// [`v0_5::VERSION`](crate::v0_5::VERSION), so that clients send them the
+    /// new messages.
+    fn vault_standard_version(&self) -> String {
+        VERSION.to_string()
+    }
+
+    /// Returns the extensions used by the vault, which are returned in the
+    /// `VaultStandardInfo` query.
+    fn extensions(&self) -> Vec<ExtensionInfo> {
+        vec![]
+    }
+
+    /// Deposits `amount` base tokens into the vault and mints vault tokens to
+    /// `recipient`. The base tokens have already been validated to be sent
+    /// with the message.
+    fn deposit(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Burns `amount` vault tokens and sends the corresponding base tokens to
+    /// `recipient`. The vault tokens have already been validated to be sent
+    /// with the message.
+    fn redeem(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Returns the amount of assets managed by the vault denominated in base
+    /// tokens.
+    fn total_assets(&self, deps: Deps, env: Env) -> StdResult<Uint128>;
+
+    /// Returns the total amount of vault tokens in circulation.
+    fn total_vault_token_supply(&self, deps: Deps, env: Env) -> StdResult<Uint128>;
+
+    /// Returns whether deposits and redemptions are currently paused. While
+    /// paused, [`execute`] rejects `Deposit` and `Redeem`.
+    fn paused(&self, _deps: Deps, _env: Env) -> StdResult<bool> {
+        Ok(false)
+    }
+
+    /// Returns the number of decimals by which vault tokens are more precise
+    /// than base tokens, used as virtual offset by
+    /// [`VaultImpl::convert_to_shares`] and [`VaultImpl::convert_to_assets`].
+    /// The share math always counts one
+    /// virtual base token and `10^decimals_offset` virtual vault tokens, which
+    /// makes the first-depositor inflation attack unprofitable. A higher offset
+    /// makes it more expensive, but means that the first deposit mints
+    /// `10^decimals_offset` vault tokens per base token. Defaults to 0.
+    fn decimals_offset(&self) -> u32 {
+        0
+    }
+
+    /// Returns the amount of vault tokens that the vault would exchange for
+    /// `amount` base tokens. Returns an error if the vault has vault tokens in
+    /// circulation but no assets, since new deposits would then be diluted
+    /// into worthless vault tokens.
+    fn convert_to_shares(&self, deps: Deps, env: Env, amount: Uint128) -> StdResult<Uint128> {
+        let total_assets = self.total_assets(deps, env.clone())?;
+        let total_supply = self.total_vault_token_supply(deps, env)?;
+        if total_assets.is_zero() && !total_supply.is_zero() {
+            return Err(StdError::generic_err(
+                "vault has vault tokens in circulation but no assets",
+            ));
+        }
+        let virtual_shares = Uint128::new(10).checked_pow(self.decimals_offset())?;
+        amount
+            .checked_multiply_ratio(
+                total_supply.checked_add(virtual_shares)?,
+                total_assets.checked_add(Uint128::one())?,
+            )
+            .map_err(|err| StdError::generic_err(err.to_string()))
+    }
+
+    /// Returns the amount of base tokens that the vault would exchange for
+    /// `amount` vault tokens.
+    fn convert_to_assets(&self, deps: Deps, env: Env, amount: Uint128) -> StdResult<Uint128> {
+        let total_assets = self.total_assets(deps, env.clone())?;
+        let total_supply = self.total_vault_token_supply(deps, env)?;
+        let virtual_shares = Uint128::new(10).checked_pow(self.decimals_offset())?;
+        amount
+            .checked_multiply_ratio(
+                total_assets.checked_add(Uint128::one())?,
+                total_supply.checked_add(virtual_shares)?,
+            )
+            .map_err(|err| StdError::generic_err(err.to_string()))
+    }
+
+    /// Returns the amount of `quote_denom` that can be exchanged for 1 unit of
+    /// vault tokens. Only the base token is supported as quote denom. This is
+    /// the plain ratio of total assets to total supply, without the virtual
+    /// offset of [`VaultImpl::convert_to_shares`].
+    fn vault_token_exchange_rate(
+        &self,
+        deps: Deps,
+        env: Env,
+        quote_denom: String,
+    ) -> StdResult<Decimal> {
+        let base_token = self.vault_info(deps, env.clone())?.base_token;
+        if quote_denom != base_token {
+            return Err(StdError::generic_err(format!(
+                "unsupported quote denom {quote_denom}"
+            )));
+        }
+        let total_assets = self.total_assets(deps, env.clone())?;
+        let total_supply = self.total_vault_token_supply(deps, env)?;
+        if total_supply.is_zero() {
+            return Ok(Decimal::one());
+        }
+        Decimal::checked_from_ratio(total_assets, total_supply)
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
+
+    /// Returns the position of `address` in the vault. The vault token balance
+    /// is read from the bank module. Vaults that implement the Lockup extension
+    /// or track cost basis should override this to fill in the remaining
+    /// fields.
+    fn user_position(
+        &self,
+        deps: Deps,
+        env: Env,
+        address: Addr,
+    ) -> StdResult<UserPositionResponse> {
+        let vault_token = self.vault_info(deps, env.clone())?.vault_token;
+        let vault_token_balance = deps.querier.query_balance(address, vault_token)?.amount;
+
+        Ok(UserPositionResponse {
+            vault_token_balance,
+            base_token_amount: self.convert_to_assets(deps, env, vault_token_balance)?,
+            unlocking_base_token_amount: Uint128::zero(),
+            claimable_base_token_amount: Uint128::zero(),
+            cost_basis: None,
+        })
+    }
+}
+
+/// Handles the `VaultExtension` variant of [`VaultStandardExecuteMsg`] for a
+/// vault with extension enum `T`. Implementations for the built-in extensions
+/// can route to [`execute_lockup`], [`execute_force_unlock`] and
+/// [`execute_keeper`].
+pub trait ExtensionExecuteImpl<T>: VaultImpl {
+    /// Executes an extension message.
+    fn execute_extension(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: T,
+    ) -> Result<Response, Self::Error>;
+}
+
+/// Handles the `VaultExtension` variant of [`VaultStandardQueryMsg`] for a
+/// vault with extension enum `T`. Implementations for the built-in extensions
+/// can route to [`query_lockup`] and [`query_keeper`].
+pub trait ExtensionQueryImpl<T>: VaultImpl {
+    /// Queries an extension message.
+    fn query_extension(&self, deps: Deps, env: Env, msg: T) -> StdResult<Binary>;
+}
+
+impl<V: VaultImpl> ExtensionExecuteImpl<Empty> for V {
+    fn execute_extension(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("vault does not implement any extensions").into())
+    }
+}
+
+impl<V: VaultImpl> ExtensionQueryImpl<Empty> for V {
+    fn query_extension(&self, _deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err(
+            "vault does not implement any extensions",
+        ))
+    }
+}
+
+/// Returns an error if deposits and redemptions of `vault` are paused.
+fn assert_not_paused<V: VaultImpl>(vault: &V, deps: Deps, env: &Env) -> StdResult<()> {
+    if vault.paused(deps, env.clone())? {
+        return Err(StdError::generic_err("vault is paused"));
+    }
+    Ok(())
+}
+
+/// Executes a [`VaultStandardExecuteMsg`] on `vault`.
+///
+/// The amount of `Deposit` and `Redeem` is read from the sent funds, which must
+/// contain exactly one coin of the base token and vault token respectively.
+/// The deprecated `amount` fields are ignored. The recipient defaults to the
+/// sender, and an event of type [`DEPOSIT_EVENT_TYPE`] or
+/// [`REDEEM_EVENT_TYPE`] is added to the response. Both are rejected while
+/// [`VaultImpl::paused`] returns true.
+pub fn execute<V, T>(
+    vault: &V,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: VaultStandardExecuteMsg<T>,
+) -> Result<Response, V::Error>
+where
+    V: ExtensionExecuteImpl<T>,
+{
+    match msg {
+        VaultStandardExecuteMsg::Deposit { recipient, .. } => {
+            assert_not_paused(vault, deps.as_ref(), &env)?;
+            let base_token = vault.vault_info(deps.as_ref(), env.clone())?.base_token;
+            let amount = must_pay_base_tokens(&info, &base_token)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            let event = Event::new(DEPOSIT_EVENT_TYPE)
+                .add_attribute(AMOUNT_ATTR_KEY, amount)
+                .add_attribute(RECIPIENT_ATTR_KEY, &recipient);
+
+            Ok(vault
+                .deposit(deps, env, info, amount, recipient)?
+                .add_event(event))
+        }
+        VaultStandardExecuteMsg::Redeem { recipient, .. } => {
+            assert_not_paused(vault, deps.as_ref(), &env)?;
+            let vault_token = vault.vault_info(deps.as_ref(), env.clone())?.vault_token;
+            let amount = must_pay_vault_tokens(&info, &vault_token)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            let event = Event::new(REDEEM_EVENT_TYPE)
+                .add_attribute(AMOUNT_ATTR_KEY, amount)
+                .add_attribute(RECIPIENT_ATTR_KEY, &recipient);
+
+            Ok(vault
+                .redeem(deps, env, info, amount, recipient)?
+                .add_event(event))
+        }
+        VaultStandardExecuteMsg::VaultExtension(msg) => {
+            vault.execute_extension(deps, env, info, msg)
+        }
+    }
+}
+
+/// Queries a [`VaultStandardQueryMsg`] on `vault`. The `VaultStandardInfo`
+/// query is answered with the version of this crate and the extensions
+/// returned by [`VaultImpl::extensions`]. The deprecated `PreviewDeposit` and
+/// `PreviewRedeem` queries return an error.
+pub fn query<V, T>(
+    vault: &V,
+    deps: Deps,
+    env: Env,
+    msg: VaultStandardQueryMsg<T>,
+) -> StdResult<Binary>
+where
+    V: ExtensionQueryImpl<T>,
+    T: JsonSchema,
+{
+    match msg {
+        VaultStandardQueryMsg::VaultStandardInfo {} => to_json_binary(&VaultStandardInfoResponse {
+            version: vault.vault_standard_version(),
+            extensions: vault.extensions(),
+        }),
+        VaultStandardQueryMsg::Info {} => to_json_binary(&vault.vault_info(deps, env)?),
+        #[allow(deprecated)]
+        VaultStandardQueryMsg::PreviewDeposit { .. }
+        | VaultStandardQueryMsg::PreviewRedeem { .. } => Err(StdError::generic_err(
+            "PreviewDeposit and PreviewRedeem are deprecated and not supported",
+        )),
+        VaultStandardQueryMsg::TotalAssets {} => to_json_binary(&vault.total_assets(deps, env)?),
+        VaultStandardQueryMsg::TotalVaultTokenSupply {} => {
+            to_json_binary(&vault.total_vault_token_supply(deps, env)?)
+        }
+        VaultStandardQueryMsg::VaultTokenExchangeRate { quote_denom } => {
+            to_json_binary(&vault.vault_token_exchange_rate(deps, env, quote_denom)?)
+        }
+        VaultStandardQueryMsg::ConvertToShares { amount } => {
+            to_json_binary(&vault.convert_to_shares(deps, env, amount)?)
+        }
+        VaultStandardQueryMsg::ConvertToAssets { amount } => {
+            to_json_binary(&vault.convert_to_assets(deps, env, amount)?)
+        }
+        VaultStandardQueryMsg::UserPosition { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&vault.user_position(deps, env, address)?)
+        }
+        VaultStandardQueryMsg::VaultState {} => {
+            let info = vault.vault_info(deps, env.clone())?;
+            to_json_binary(&VaultStateResponse {
+                total_assets: vault.total_assets(deps, env.clone())?,
+                total_vault_token_supply: vault.total_vault_token_supply(deps, env.clone())?,
+                vault_token_exchange_rate: vault.vault_token_exchange_rate(
+                    deps,
+                    env.clone(),
+                    info.base_token.clone(),
+                )?,
+                paused: vault.paused(deps, env)?,
+                version: vault.vault_standard_version(),
+                base_token: info.base_token,
+                vault_token: info.vault_token,
+            })
+        }
+        VaultStandardQueryMsg::VaultExtension(msg) => vault.query_extension(deps, env, msg),
+    }
+}
+
+/// The functionality of a vault that implements the Lockup extension.
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub trait LockupImpl: VaultImpl {
+    /// Burns `amount` vault tokens sent by the caller and creates an unlocking
+    /// position for the corresponding base tokens, in lockup tier `tier` if
+    /// set. Must emit an event with type
+    /// `UNLOCKING_POSITION_CREATED_EVENT_TYPE` containing the lockup ID.
+    fn unlock(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        tier: Option<u64>,
+    ) -> Result<Response, Self::Error>;
+
+    /// Unlocks `amount` vault tokens without performing any other side effects
+    /// that might cause the transaction to fail.
+    fn emergency_unlock(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, Self::Error>;
+
+    /// Sends the base tokens of the matured unlocking position with ID
+    /// `lockup_id` to `recipient`. Must check that the caller owns the
+    /// position.
+    fn withdraw_unlocked(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        lockup_id: u64,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Withdraws the matured unlocking positions with IDs `lockup_ids` to
+    /// `recipient`. By default calls [`LockupImpl::withdraw_unlocked`] for
+    /// each position and merges the responses.
+    fn withdraw_unlocked_batch(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        lockup_ids: Vec<u64>,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error> {
+        let mut response = Response::new();
+        for lockup_id in lockup_ids {
+            let res = self.withdraw_unlocked(
+                deps.branch(),
+                env.clone(),
+                info.clone(),
+                lockup_id,
+                recipient.clone(),
+            )?;
+            response = response
+                .add_submessages(res.messages)
+                .add_attributes(res.attributes)
+                .add_events(res.events);
+        }
+        Ok(response)
+    }
+
+    /// Withdraws all matured unlocking positions of the caller to
+    /// `recipient`. By default withdraws the positions returned by
+    /// [`LockupImpl::claimable_unlocked`] with
+    /// [`LockupImpl::withdraw_unlocked_batch`].
+    fn withdraw_all_unlocked(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error> {
+        let claimable = self.claimable_unlocked(deps.as_ref(), env.clone(), info.sender.clone())?;
+        self.withdraw_unlocked_batch(deps, env, info, claimable.lockup_ids, recipient)
+    }
+
+    /// Transfers the unlocking position with ID `lockup_id` to `recipient`.
+    /// Must check that the caller owns the position. Not supported by default,
+    /// and rejected by [`execute_lockup_nft`] for vaults that enable the
+    /// LockupNft extension.
+    fn transfer_unlocking_position(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _lockup_id: u64,
+        _recipient: Addr,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("TransferUnlockingPosition is not supported").into())
+    }
+
+    /// Splits `amount` base tokens off the unlocking position with ID
+    /// `lockup_id` into a new position. Must check that the caller owns the
+    /// position. Not supported by default.
+    fn split_unlocking_position(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _lockup_id: u64,
+        _amount: Uint128,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("SplitUnlockingPosition is not supported").into())
+    }
+
+    /// Merges the unlocking positions with IDs `ids` into the first of them.
+    /// Must check that the caller owns all positions. `ids` are checked to be
+    /// unique before this is called. Not supported by default.
+    fn merge_unlocking_positions(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _ids: Vec<u64>,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("MergeUnlockingPositions is not supported").into())
+    }
+
+    /// Removes the unlocking position with ID `lockup_id` and mints vault
+    /// tokens to the caller according to the `CancelUnlockMode` of the vault.
+    /// Must check that the caller owns the position and emit an event with
+    /// type `UNLOCK_CANCELLED_EVENT_TYPE`. Not supported by default.
+    fn cancel_unlock(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _lockup_id: u64,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("CancelUnlock is not supported").into())
+    }
+
+    /// Withdraws the unlocking position with ID `lockup_id` before it has
+    /// finished unlocking, taking a penalty and sending the rest of the base
+    /// tokens to `recipient`. Must check that the caller owns the position and
+    /// emit an event with type `EARLY_WITHDRAW_EVENT_TYPE`. Not supported by
+    /// default.
+    fn early_withdraw(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _lockup_id: u64,
+        _recipient: Addr,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("EarlyWithdraw is not supported").into())
+    }
+
+    /// Returns the unlocking positions of `owner`, ordered by ID.
+    fn unlocking_positions(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>>;
+
+    /// Returns the unlocking positions of `owner` that have finished
+    /// unlocking. By default pages through
+    /// [`LockupImpl::unlocking_positions`] with its default limit, until it
+    /// returns a page shorter than the first one. The cost is linear in the
+    /// number of unlocking positions of `owner`, so vaults that let owners
+    /// have many positions should override this, e.g. using an index by
+    /// release time.
+    fn claimable_unlocked(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: Addr,
+    ) -> StdResult<ClaimableUnlockedResponse> {
+        let mut claimable = ClaimableUnlockedResponse {
+            lockup_ids: vec![],
+            base_token_amount: Uint128::zero(),
+        };
+        let mut start_after = None;
+        let mut page_size = None;
+        loop {
+            let positions =
+                self.unlocking_positions(deps, env.clone(), owner.clone(), start_after, None)?;
+            let Some(last) = positions.last().map(|position| position.id) else {
+                break;
+            };
+            if start_after.is_some_and(|start_after| positions[0].id <= start_after) {
+                return Err(StdError::generic_err(
+                    "unlocking positions must be returned in ascending order of ID after \
+                     start_after",
+                ));
+            }
+            let page_size = *page_size.get_or_insert(positions.len());
+            let is_last_page = positions.len() < page_size;
+            start_after = Some(last);
+            for position in positions {
+                if position.release_at.is_expired(&env.block) {
+                    claimable.lockup_ids.push(position.id);
+                    claimable.base_token_amount = claimable
+                        .base_token_amount
+                        .checked_add(position.base_token_amount)?;
+                }
+            }
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(claimable)
+    }
+
+    /// Returns the unlocking positions of all owners, ordered by ID. Not
+    /// supported by default.
+    fn all_unlocking_positions(
+        &self,
+        _deps: Deps,
+        _env: Env,
+        _start_after: Option<u64>,
+        _limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        Err(StdError::generic_err(
+            "AllUnlockingPositions is not supported",
+        ))
+    }
+
+    /// Returns the unlocking positions of all owners that finish unlocking at
+    /// or before `time`, ordered by release time and then by ID. Positions
+    /// with a `release_at` in block height must not be returned. Vaults
+    /// storing their positions in `storage::UnlockingPositions` can implement
+    /// this with `UnlockingPositions::maturing_before`. Not supported by
+    /// default.
+    fn unlocking_positions_maturing_before(
+        &self,
+        _deps: Deps,
+        _env: Env,
+        _time: Timestamp,
+        _start_after: Option<(Timestamp, u64)>,
+        _limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        Err(StdError::generic_err(
+            "UnlockingPositionsMaturingBefore is not supported",
+        ))
+    }
+
+    /// Returns the sum of the base tokens of all unlocking positions. Not
+    /// supported by default.
+    fn total_unlocking(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+        Err(StdError::generic_err("TotalUnlocking is not supported"))
+    }
+
+    /// Returns the unlocking position with ID `lockup_id`.
+    fn unlocking_position(
+        &self,
+        deps: Deps,
+        env: Env,
+        lockup_id: u64,
+    ) -> StdResult<UnlockingPosition>;
+
+    /// Returns the lockup duration of the vault.
+    fn lockup_duration(&self, deps: Deps, env: Env) -> StdResult<Duration>;
+
+    /// Returns how the vault mints vault tokens on `CancelUnlock`. Not
+    /// supported by default.
+    fn cancel_unlock_mode(&self, _deps: Deps, _env: Env) -> StdResult<CancelUnlockMode> {
+        Err(StdError::generic_err("CancelUnlock is not supported"))
+    }
+
+    /// Returns the early withdrawal configuration of the vault. Not supported
+    /// by default.
+    fn early_withdraw_config(&self, _deps: Deps, _env: Env) -> StdResult<EarlyWithdrawConfig> {
+        Err(StdError::generic_err("EarlyWithdraw is not supported"))
+    }
+
+    /// Returns the result of withdrawing the unlocking position with ID
+    /// `lockup_id` early in the current block. By default computed with
+    /// [`preview_early_withdraw`] from the position, the duration of its tier
+    /// and the configured penalty schedule.
+    fn preview_early_withdraw(
+        &self,
+        deps: Deps,
+        env: Env,
+        lockup_id: u64,
+    ) -> StdResult<EarlyWithdrawPreview> {
+        let config = self.early_withdraw_config(deps, env.clone())?;
+        let position = self.unlocking_position(deps, env.clone(), lockup_id)?;
+        let duration = match position.tier {
+            Some(tier_id) => self
+                .lockup_tiers(deps, env.clone())?
+                .into_iter()
+                .find(|tier| tier.id == tier_id)
+                .map(|tier| tier.duration)
+                .ok_or_else(|| StdError::generic_err(format!("lockup tier {tier_id} not found")))?,
+            None => self.lockup_duration(deps, env.clone())?,
+        };
+        preview_early_withdraw(&position, &duration, &config.schedule, &env.block)
+    }
+
+    /// Returns the lockup tiers offered by the vault. Empty by default.
+    fn lockup_tiers(&self, _deps: Deps, _env: Env) -> StdResult<Vec<LockupTier>> {
+        Ok(vec![])
+    }
+}
+
+/// Executes a [`LockupExecuteMsg`] on `vault`. The vault tokens of `Unlock`
+/// are read from the sent funds, and the variants other than `Unlock` and
+/// `EmergencyUnlock` must not be sent any funds.
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub fn execute_lockup<V: LockupImpl>(
+    vault: &V,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: LockupExecuteMsg,
+) -> Result<Response, V::Error> {
+    match msg {
+        LockupExecuteMsg::Unlock { tier, .. } => {
+            let vault_token = vault.vault_info(deps.as_ref(), env.clone())?.vault_token;
+            let amount = must_pay_vault_tokens(&info, &vault_token)?;
+            vault.unlock(deps, env, info, amount, tier)
+        }
+        LockupExecuteMsg::EmergencyUnlock { amount } => {
+            vault.emergency_unlock(deps, env, info, amount)
+        }
+        LockupExecuteMsg::WithdrawUnlocked {
+            recipient,
+            lockup_id,
+        } => {
+            nonpayable(&info)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.withdraw_unlocked(deps, env, info, lockup_id, recipient)
+        }
+        LockupExecuteMsg::WithdrawAllUnlocked { recipient } => {
+            nonpayable(&info)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.withdraw_all_unlocked(deps, env, info, recipient)
+        }
+        LockupExecuteMsg::WithdrawUnlockedBatch {
+            lockup_ids,
+            recipient,
+        } => {
+            nonpayable(&info)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.withdraw_unlocked_batch(deps, env, info, lockup_ids, recipient)
+        }
+        LockupExecuteMsg::TransferUnlockingPosition {
+            lockup_id,
+            recipient,
+        } => {
+            nonpayable(&info)?;
+            let recipient = deps.api.addr_validate(&recipient)?;
+            vault.transfer_unlocking_position(deps, env, info, lockup_id, recipient)
+        }
+        LockupExecuteMsg::SplitUnlockingPosition { lockup_id, amount } => {
+            nonpayable(&info)?;
+            vault.split_unlocking_position(deps, env, info, lockup_id, amount)
+        }
+        LockupExecuteMsg::MergeUnlockingPositions { ids } => {
+            nonpayable(&info)?;
+            if let Some(id) = ids
+                .iter()
+                .enumerate()
+                .find_map(|(i, id)| ids[..i].contains(id).then_some(id))
+            {
+                return Err(StdError::generic_err(format!(
+                    "unlocking position {id} can not be merged more than once"
+                ))
+                .into());
+            }
+            vault.merge_unlocking_positions(deps, env, info, ids)
+        }
+        LockupExecuteMsg::CancelUnlock { lockup_id } => {
+            nonpayable(&info)?;
+            vault.cancel_unlock(deps, env, info, lockup_id)
+        }
+        LockupExecuteMsg::EarlyWithdraw {
+            lockup_id,
+            recipient,
+        } => {
+            nonpayable(&info)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.early_withdraw(deps, env, info, lockup_id, recipient)
+        }
+    }
+}
+
+/// Queries a [`LockupQueryMsg`] on `vault`.
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub fn query_lockup<V: LockupImpl>(
+    vault: &V,
+    deps: Deps,
+    env: Env,
+    msg: LockupQueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        LockupQueryMsg::UnlockingPositions {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            to_json_binary(&vault.unlocking_positions(deps, env, owner, start_after, limit)?)
+        }
+        LockupQueryMsg::ClaimableUnlocked { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            to_json_binary(&vault.claimable_unlocked(deps, env, owner)?)
+        }
+        LockupQueryMsg::UnlockingPosition { lockup_id } => {
+            to_json_binary(&vault.unlocking_position(deps, env, lockup_id)?)
+        }
+        LockupQueryMsg::AllUnlockingPositions { start_after, limit } => {
+            to_json_binary(&vault.all_unlocking_positions(deps, env, start_after, limit)?)
+        }
+        LockupQueryMsg::UnlockingPositionsMaturingBefore {
+            time,
+            start_after,
+            limit,
+        } => to_json_binary(&vault.unlocking_positions_maturing_before(
+            deps,
+            env,
+            time,
+            start_after,
+            limit,
+        )?),
+        LockupQueryMsg::TotalUnlocking {} => to_json_binary(&vault.total_unlocking(deps, env)?),
+        LockupQueryMsg::LockupDuration {} => to_json_binary(&vault.lockup_duration(deps, env)?),
+        LockupQueryMsg::LockupTiers {} => to_json_binary(&vault.lockup_tiers(deps, env)?),
+        LockupQueryMsg::PreviewEarlyWithdraw { lockup_id } => {
+            to_json_binary(&vault.preview_early_withdraw(deps, env, lockup_id)?)
+        }
+        LockupQueryMsg::CancelUnlockMode {} => {
+            to_json_binary(&vault.cancel_unlock_mode(deps, env)?)
+        }
+        LockupQueryMsg::EarlyWithdrawConfig {} => {
+            to_json_binary(&vault.early_withdraw_config(deps, env)?)
+        }
+    }
+}
+
+/// The functionality of a vault that implements the LockupNft extension on top
+/// of the Lockup extension. The owner of an unlocking position is the owner of
+/// its token in the cw721 contract, see [`LockupNft`]. Implementations must
+/// mint a token in [`LockupImpl::unlock`] and
+/// [`LockupImpl::split_unlocking_position`], burn the tokens of the positions
+/// they remove, and resolve owners through the cw721 contract in the lockup
+/// queries.
+#[cfg(feature = "lockup-nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup-nft")))]
+pub trait LockupNftImpl: LockupImpl {
+    /// Returns the cw721 contract in which the unlocking positions are minted.
+    fn lockup_nft(&self, deps: Deps, env: Env) -> StdResult<LockupNft>;
+
+    /// Sends the base tokens of the matured unlocking position with ID
+    /// `lockup_id` to `recipient` and burns its token, which `owner` has sent
+    /// to the vault.
+    fn withdraw_received_unlocked(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        lockup_id: u64,
+        owner: Addr,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+}
+
+/// Executes a [`LockupExecuteMsg`] on a vault that enables the LockupNft
+/// extension. `TransferUnlockingPosition` is rejected, since positions are
+/// transferred by transferring their token. For the variants that take lockup
+/// IDs, checks that the caller owns the tokens in the cw721 contract and that
+/// the vault is approved for the tokens of the positions that are removed, so
+/// that it can burn them. Then the message is executed with
+/// [`execute_lockup`].
+#[cfg(feature = "lockup-nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup-nft")))]
+pub fn execute_lockup_nft<V: LockupNftImpl>(
+    vault: &V,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: LockupExecuteMsg,
+) -> Result<Response, V::Error> {
+    let (owned, removed): (&[u64], &[u64]) = match &msg {
+        LockupExecuteMsg::TransferUnlockingPosition { .. } => {
+            return Err(StdError::generic_err(
+                "TransferUnlockingPosition is not supported with the lockup-nft extension, \
+                transfer the token of the unlocking position instead",
+            )
+            .into())
+        }
+        LockupExecuteMsg::WithdrawUnlocked { lockup_id, .. }
+        | LockupExecuteMsg::CancelUnlock { lockup_id }
+        | LockupExecuteMsg::EarlyWithdraw { lockup_id, .. } => (
+            std::slice::from_ref(lockup_id),
+            std::slice::from_ref(lockup_id),
+        ),
+        LockupExecuteMsg::WithdrawUnlockedBatch { lockup_ids, .. } => (lockup_ids, lockup_ids),
+        LockupExecuteMsg::SplitUnlockingPosition { lockup_id, .. } => {
+            (std::slice::from_ref(lockup_id), &[])
+        }
+        LockupExecuteMsg::MergeUnlockingPositions { ids } => {
+            (ids, ids.get(1..).unwrap_or_default())
+        }
+        _ => (&[], &[]),
+    };
+    if !owned.is_empty() {
+        let nft = vault.lockup_nft(deps.as_ref(), env.clone())?;
+        for lockup_id in owned {
+            nft.assert_owner(&deps.querier, &info, *lockup_id)?;
+        }
+        for lockup_id in removed {
+            nft.assert_approved(&deps.querier, &env.contract.address, *lockup_id)?;
+        }
+    }
+    execute_lockup(vault, deps, env, info, msg)
+}
+
+/// Handles the `ReceiveNft` message of the cw721 contract of a vault that
+/// enables the LockupNft extension. Checks that the message was sent by the
+/// cw721 contract and withdraws the received position with
+/// [`LockupNftImpl::withdraw_received_unlocked`]. If no recipient is set, the
+/// base tokens are sent to the previous owner of the token.
+#[cfg(feature = "lockup-nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup-nft")))]
+pub fn receive_nft<V: LockupNftImpl>(
+    vault: &V,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, V::Error> {
+    nonpayable(&info)?;
+    let nft = vault.lockup_nft(deps.as_ref(), env.clone())?;
+    let (lockup_id, owner, msg) = nft.receive(&info, msg)?;
+    match msg {
+        LockupNftReceiveMsg::WithdrawUnlocked { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => owner.clone(),
+            };
+            vault.withdraw_received_unlocked(deps, env, lockup_id, owner, recipient)
+        }
+    }
+}
+
+/// Queries a [`LockupNftQueryMsg`] on `vault`.
+#[cfg(feature = "lockup-nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup-nft")))]
+pub fn query_lockup_nft<V: LockupNftImpl>(
+    vault: &V,
+    deps: Deps,
+    env: Env,
+    msg: LockupNftQueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        LockupNftQueryMsg::NftContract {} => to_json_binary(vault.lockup_nft(deps, env)?.addr()),
+    }
+}
+
+/// The functionality of a vault that implements the ForceUnlock extension.
+/// Implementations must check that the caller is whitelisted.
+#[cfg(feature = "force-unlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "force-unlock")))]
+pub trait ForceUnlockImpl: VaultImpl {
+    /// Burns `amount` vault tokens sent by the caller and immediately sends
+    /// the corresponding base tokens to `recipient`, bypassing the lockup.
+    fn force_redeem(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Withdraws `amount` base tokens, or the entire position if `None`, from
+    /// the unlocking position with ID `lockup_id` and sends them to
+    /// `recipient`.
+    fn force_withdraw_unlocking(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        lockup_id: u64,
+        amount: Option<Uint128>,
+        recipient: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Updates the whitelist of addresses that can force unlock. Must check
+    /// that the caller is the vault admin.
+    fn update_force_withdraw_whitelist(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        add_addresses: Vec<Addr>,
+        remove_addresses: Vec<Addr>,
+    ) -> Result<Response, Self::Error>;
+}
+
+/// Executes a [`ForceUnlockExecuteMsg`] on `vault`. The vault tokens of
+/// `ForceRedeem` are read from the sent funds, the other variants must not be
+/// sent any funds.
+#[cfg(feature = "force-unlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "force-unlock")))]
+pub fn execute_force_unlock<V: ForceUnlockImpl>(
+    vault: &V,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ForceUnlockExecuteMsg,
+) -> Result<Response, V::Error> {
+    match msg {
+        ForceUnlockExecuteMsg::ForceRedeem { recipient, .. } => {
+            let vault_token = vault.vault_info(deps.as_ref(), env.clone())?.vault_token;
+            let amount = must_pay_vault_tokens(&info, &vault_token)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.force_redeem(deps, env, info, amount, recipient)
+        }
+        ForceUnlockExecuteMsg::ForceWithdrawUnlocking {
+            lockup_id,
+            amount,
+            recipient,
+        } => {
+            nonpayable(&info)?;
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.force_withdraw_unlocking(deps, env, info, lockup_id, amount, recipient)
+        }
+        ForceUnlockExecuteMsg::UpdateForceWithdrawWhitelist {
+            add_addresses,
+            remove_addresses,
+        } => {
+            nonpayable(&info)?;
+            let add_addresses = validate_addresses(deps.as_ref(), &add_addresses)?;
+            let remove_addresses = validate_addresses(deps.as_ref(), &remove_addresses)?;
+            vault.update_force_withdraw_whitelist(deps, env, info, add_addresses, remove_addresses)
+        }
+    }
+}
+
+/// The functionality of a vault that implements the Keeper extension.
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub trait KeeperImpl: VaultImpl {
+    /// Whitelists `keeper` for the job with ID `job_id`, or adds it to the
+    /// global keeper whitelist if `job_id` is `None`. Must check that the
+    /// caller is the vault admin. Vaults storing their whitelists in
+    /// `storage::KeeperWhitelists` can implement this with
+    /// `KeeperWhitelists::insert`.
+    fn whitelist_keeper(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Option<u64>,
+        keeper: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Removes `keeper` from the whitelist of the job with ID `job_id`, or
+    /// from the global keeper whitelist if `job_id` is `None`. Must check that
+    /// the caller is the vault admin.
+    fn blacklist_keeper(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Option<u64>,
+        keeper: Addr,
+    ) -> Result<Response, Self::Error>;
+
+    /// Adds `add` to and removes `remove` from the whitelist of the job with
+    /// ID `job_id`, or the global keeper whitelist if `job_id` is `None`. By
+    /// default calls [`KeeperImpl::whitelist_keeper`] and
+    /// [`KeeperImpl::blacklist_keeper`] for each address.
+    fn update_keeper_whitelist(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Option<u64>,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    ) -> Result<Response, Self::Error> {
+        let mut response = Response::new();
+        for keeper in add {
+            let res =
+                self.whitelist_keeper(deps.branch(), env.clone(), info.clone(), job_id, keeper)?;
+            response = response
+                .add_submessages(res.messages)
+                .add_attributes(res.attributes)
+                .add_events(res.events);
+        }
+        for keeper in remove {
+            let res =
+                self.blacklist_keeper(deps.branch(), env.clone(), info.clone(), job_id, keeper)?;
+            response = response
+                .add_submessages(res.messages)
+                .add_attributes(res.attributes)
+                .add_events(res.events);
+        }
+        Ok(response)
+    }
+
+    /// Adds a new keeper job with the given configuration, e.g. with
+    /// [`KeeperJobConfig::into_job`]. Must check that the caller is the vault
+    /// admin and emit an event with type `KEEPER_JOB_ADDED_EVENT_TYPE`. The
+    /// configuration has already been validated with
+    /// [`KeeperJobConfig::validate`]. Not supported by default.
+    fn add_keeper_job(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _config: KeeperJobConfig,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("AddKeeperJob is not supported").into())
+    }
+
+    /// Removes the keeper job with ID `job_id`. Must check that the caller is
+    /// the vault admin and emit an event with type
+    /// `KEEPER_JOB_REMOVED_EVENT_TYPE`. Not supported by default.
+    fn remove_keeper_job(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _job_id: u64,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("RemoveKeeperJob is not supported").into())
+    }
+
+    /// Performs the job with ID `job_id`. The job has already been checked to
+    /// be ready and the caller to be allowed to execute it. The execution is
+    /// recorded on the job by [`execute_keeper`] afterwards.
+    fn execute_job(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: u64,
+    ) -> Result<Response, Self::Error>;
+
+    /// Returns the execution for which `keeper` was last paid a bounty for the
+    /// job with ID `job_id`, used for the cooldown of the bounty. Returns
+    /// `None` by default.
+    fn keeper_last_paid(
+        &self,
+        _deps: Deps,
+        _env: Env,
+        _job_id: u64,
+        _keeper: &Addr,
+    ) -> StdResult<Option<KeeperJobExecution>> {
+        Ok(None)
+    }
+
+    /// Returns the yield harvested by the execution of `job` that just
+    /// finished, used to compute `YieldShare` bounties. Vaults with such
+    /// bounties must implement this, e.g. by storing the harvested amount in
+    /// [`KeeperImpl::execute_job`]. Returns no yield by default.
+    fn harvested_yield(&self, _deps: Deps, _env: Env, _job: &KeeperJob) -> StdResult<Vec<Coin>> {
+        Ok(vec![])
+    }
+
+    /// Adds `rewards` to the rewards accrued by `keeper` as the bounty for
+    /// executing the job with ID `job_id`, and records the payment for
+    /// [`KeeperImpl::keeper_last_paid`]. Called by [`execute_keeper`] when the
+    /// bounty of a job is payable. Returns whether the rewards were accrued.
+    /// By default bounties are not paid and `false` is returned, so that jobs
+    /// with a bounty can still be executed.
+    fn accrue_keeper_rewards(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _job_id: u64,
+        _keeper: &Addr,
+        _rewards: &[Coin],
+    ) -> StdResult<bool> {
+        Ok(false)
+    }
+
+    /// Sends the keeper rewards accrued by the caller to `recipient`. Must
+    /// emit an event with type `KEEPER_REWARDS_CLAIMED_EVENT_TYPE`. Not
+    /// supported by default.
+    fn claim_keeper_rewards(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _recipient: Addr,
+    ) -> Result<Response, Self::Error> {
+        Err(StdError::generic_err("ClaimKeeperRewards is not supported").into())
+    }
+
+    /// Saves `job`, replacing the stored job with the same ID. Called by
+    /// [`execute_keeper`] to record the execution of a job with
+    /// [`KeeperJob::record_execution`].
+    fn save_keeper_job(&self, deps: DepsMut, env: Env, job: &KeeperJob) -> StdResult<()>;
+
+    /// Returns the keeper jobs of the vault, ordered by ID.
+    fn keeper_jobs(
+        &self,
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<KeeperJob>>;
+
+    /// Returns whether the job with ID `job_id` can be executed. Jobs with an
+    /// `interval` should not be ready before their `next_executable_at`, see
+    /// [`KeeperJob::is_due`].
+    fn keeper_job_ready(&self, deps: Deps, env: Env, job_id: u64) -> StdResult<bool>;
+
+    /// Returns the keeper job with ID `job_id`. By default queries the first
+    /// job after `job_id - 1` with [`KeeperImpl::keeper_jobs`], which relies
+    /// on the jobs being ordered by ID.
+    fn keeper_job(&self, deps: Deps, env: Env, job_id: u64) -> StdResult<KeeperJob> {
+        self.keeper_jobs(deps, env, job_id.checked_sub(1), Some(1))?
+            .into_iter()
+            .find(|job| job.id == job_id)
+            .ok_or_else(|| StdError::not_found(format!("keeper job {job_id}")))
+    }
+
+    /// Returns whether `keeper` is on the whitelist of the job with ID
+    /// `job_id`, or on the global keeper whitelist if `job_id` is `None`.
+    /// Vaults storing their whitelists in `storage::KeeperWhitelists` can
+    /// implement this with `KeeperWhitelists::contains`.
+    fn keeper_whitelist_contains(
+        &self,
+        deps: Deps,
+        env: Env,
+        job_id: Option<u64>,
+        keeper: &Addr,
+    ) -> StdResult<bool>;
+
+    /// Returns the whitelisted keepers of the job with ID `job_id`, or the
+    /// global keeper whitelist if `job_id` is `None`, ordered by address.
+    /// Vaults storing their whitelists in `storage::KeeperWhitelists` can
+    /// implement this with `KeeperWhitelists::range`.
+    fn whitelisted_keepers(
+        &self,
+        deps: Deps,
+        env: Env,
+        job_id: Option<u64>,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>>;
+
+    /// Returns whether `keeper` may execute `job` if the job has a whitelist.
+    /// By default checks the global keeper whitelist and the whitelist of the
+    /// job with [`KeeperImpl::keeper_whitelist_contains`].
+    fn is_whitelisted_keeper(
+        &self,
+        deps: Deps,
+        env: Env,
+        job: &KeeperJob,
+        keeper: &Addr,
+    ) -> StdResult<bool> {
+        Ok(
+            self.keeper_whitelist_contains(deps, env.clone(), None, keeper)?
+                || self.keeper_whitelist_contains(deps, env, Some(job.id), keeper)?,
+        )
+    }
+
+    /// Returns the readiness and schedule of the job with ID `job_id`.
+    fn keeper_job_status(&self, deps: Deps, env: Env, job_id: u64) -> StdResult<KeeperJobStatus> {
+        let job = self.keeper_job(deps, env.clone(), job_id)?;
+        Ok(KeeperJobStatus {
+            job_id,
+            ready: self.keeper_job_ready(deps, env, job_id)?,
+            last_executed: job.last_executed,
+            next_executable_at: job.next_executable_at,
+        })
+    }
+
+    /// Returns the rewards accrued by `keeper`. Returns no rewards by default.
+    fn keeper_rewards(&self, _deps: Deps, _env: Env, _keeper: Addr) -> StdResult<Vec<Coin>> {
+        Ok(vec![])
+    }
+}
+
+/// Executes a [`KeeperExecuteMsg`] on `vault`. Before `ExecuteJob` is passed
+/// on to [`KeeperImpl::execute_job`], the job is checked to be ready and, if
+/// the job has a whitelist, the caller to be whitelisted according to
+/// [`KeeperImpl::is_whitelisted_keeper`]. After a successful execution, it is
+/// recorded on the job and saved with [`KeeperImpl::save_keeper_job`]. If the
+/// job has a bounty that was payable according to
+/// [`KeeperBounty::is_payable`](crate::extensions::keeper::KeeperBounty::is_payable)
+/// before the execution, the payout is accrued to the caller with
+/// [`KeeperImpl::accrue_keeper_rewards`] and, if it was accrued, an event of
+/// type [`KEEPER_BOUNTY_PAID_EVENT_TYPE`] is added to the response.
+///
+/// The configuration of `AddKeeperJob` is validated with
+/// [`KeeperJobConfig::validate`].
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub fn execute_keeper<V: KeeperImpl>(
+    vault: &V,
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: KeeperExecuteMsg,
+) -> Result<Response, V::Error> {
+    nonpayable(&info)?;
+    match msg {
+        KeeperExecuteMsg::WhitelistKeeper { job_id, keeper } => {
+            let keeper = deps.api.addr_validate(&keeper)?;
+            vault.whitelist_keeper(deps, env, info, Some(job_id), keeper)
+        }
+        KeeperExecuteMsg::BlacklistKeeper { job_id, keeper } => {
+            let keeper = deps.api.addr_validate(&keeper)?;
+            vault.blacklist_keeper(deps, env, info, Some(job_id), keeper)
+        }
+        KeeperExecuteMsg::UpdateKeeperWhitelist {
+            job_id,
+            add,
+            remove,
+        } => {
+            let add = validate_addresses(deps.as_ref(), &add)?;
+            let remove = validate_addresses(deps.as_ref(), &remove)?;
+            vault.update_keeper_whitelist(deps, env, info, job_id, add, remove)
+        }
+        KeeperExecuteMsg::AddKeeperJob { config } => {
+            config.validate()?;
+            vault.add_keeper_job(deps, env, info, config)
+        }
+        KeeperExecuteMsg::RemoveKeeperJob { job_id } => {
+            vault.remove_keeper_job(deps, env, info, job_id)
+        }
+        KeeperExecuteMsg::ExecuteJob { job_id } => {
+            let job = vault.keeper_job(deps.as_ref(), env.clone(), job_id)?;
+            if job.whitelist
+                && !vault.is_whitelisted_keeper(deps.as_ref(), env.clone(), &job, &info.sender)?
+            {
+                return Err(StdError::generic_err(format!(
+                    "{} is not whitelisted for keeper job {job_id}",
+                    info.sender
+                ))
+                .into());
+            }
+            if !vault.keeper_job_ready(deps.as_ref(), env.clone(), job_id)? {
+                return Err(
+                    StdError::generic_err(format!("keeper job {job_id} is not ready")).into(),
+                );
+            }
+            let keeper = info.sender.clone();
+            let bounty = match &job.bounty {
+                Some(bounty) => {
+                    let last_paid =
+                        vault.keeper_last_paid(deps.as_ref(), env.clone(), job_id, &keeper)?;
+                    bounty
+                        .is_payable(&job, last_paid.as_ref(), &env.block)
+                        .then(|| bounty.clone())
+                }
+                None => None,
+            };
+
+            let mut res = vault.execute_job(deps.branch(), env.clone(), info, job_id)?;
+            let mut job = vault.keeper_job(deps.as_ref(), env.clone(), job_id)?;
+            job.record_execution(&env.block);
+            vault.save_keeper_job(deps.branch(), env.clone(), &job)?;
+
+            if let Some(bounty) = bounty {
+                let harvested = vault.harvested_yield(deps.as_ref(), env.clone(), &job)?;
+                let rewards = bounty.payout(&harvested)?;
+                if !rewards.is_empty()
+                    && vault.accrue_keeper_rewards(deps, env, job_id, &keeper, &rewards)?
+                {
+                    res = res.add_event(
+                        Event::new(KEEPER_BOUNTY_PAID_EVENT_TYPE)
+                            .add_attribute(JOB_ID_ATTR_KEY, job_id.to_string())
+                            .add_attribute(KEEPER_ATTR_KEY, keeper)
+                            .add_attribute(
+                                REWARDS_ATTR_KEY,
+                                rewards
+                                    .iter()
+                                    .map(Coin::to_string)
+                                    .collect::<Vec<_>>()
+                                    .join(","),
+                            ),
+                    );
+                }
+            }
+            Ok(res)
+        }
+        KeeperExecuteMsg::ClaimKeeperRewards { recipient } => {
+            let recipient = resolve_recipient(deps.api, &info, recipient)?;
+            vault.claim_keeper_rewards(deps, env, info, recipient)
+        }
+    }
+}
+
+/// Queries a [`KeeperQueryMsg`] on `vault`.
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub fn query_keeper<V: KeeperImpl>(
+    vault: &V,
+    deps: Deps,
+    env: Env,
+    msg: KeeperQueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        KeeperQueryMsg::KeeperJobs { start_after, limit } => {
+            to_json_binary(&vault.keeper_jobs(deps, env, start_after, limit)?)
+        }
+        KeeperQueryMsg::WhitelistedKeepers {
+            job_id,
+            start_after,
+            limit,
+        } => {
+            let start_after = start_after
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?;
+            to_json_binary(&vault.whitelisted_keepers(deps, env, job_id, start_after, limit)?)
+        }
+        KeeperQueryMsg::KeeperJobReady { job_id } => {
+            to_json_binary(&vault.keeper_job_ready(deps, env, job_id)?)
+        }
+        KeeperQueryMsg::KeeperJobStatus { job_id } => {
+            to_json_binary(&vault.keeper_job_status(deps, env, job_id)?)
+        }
+        KeeperQueryMsg::KeeperRewards { keeper } => {
+            let keeper = deps.api.addr_validate(&keeper)?;
+            to_json_binary(&vault.keeper_rewards(deps, env, keeper)?)
+        }
+    }
+}
+
+#[cfg(any(feature = "force-unlock", feature = "keeper"))]
+fn validate_addresses(deps: Deps, addresses: &[String]) -> StdResult<Vec<Addr>> {
+    addresses
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, Coin};
+
+    const BASE_TOKEN: &str = "base";
+    const VAULT_TOKEN: &str = "vault";
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        Std(StdError),
+        Payment(PaymentError),
+    }
+
+    impl From<StdError> for TestError {
+        fn from(err: StdError) -> Self {
+            Self::Std(err)
+        }
+    }
+
+    impl From<PaymentError> for TestError {
+        fn from(err: PaymentError) -> Self {
+            Self::Payment(err)
+        }
+    }
+
+    #[derive(Default)]
+    struct TestVault {
+        total_assets: u128,
+        total_supply: u128,
+        paused: bool,
+        decimals_offset: u32,
+    }
+
+    impl VaultImpl for TestVault {
+        type Error = TestError;
+
+        fn vault_info(&self, _deps: Deps, _env: Env) -> StdResult<VaultInfoResponse> {
+            Ok(VaultInfoResponse {
+                base_token: BASE_TOKEN.to_string(),
+                vault_token: VAULT_TOKEN.to_string(),
+            })
+        }
+
+        fn deposit(
+            &self,
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _amount: Uint128,
+            _recipient: Addr,
+        ) -> Result<Response, TestError> {
+            Ok(Response::new())
+        }
+
+        fn redeem(
+            &self,
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _amount: Uint128,
+            _recipient: Addr,
+        ) -> Result<Response, TestError> {
+            Ok(Response::new())
+        }
+
+        fn total_assets(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+            Ok(self.total_assets.into())
+        }
+
+        fn total_vault_token_supply(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+            Ok(self.total_supply.into())
+        }
+
+        fn paused(&self, _deps: Deps, _env: Env) -> StdResult<bool> {
+            Ok(self.paused)
+        }
+
+        fn decimals_offset(&self) -> u32 {
+            self.decimals_offset
+        }
+    }
+
+    fn execute_msg(
+        vault: &TestVault,
+        msg: VaultStandardExecuteMsg<Empty>,
+        funds: &[Coin],
+    ) -> Result<Response, TestError> {
+        let mut deps = mock_dependencies();
+        execute(
+            vault,
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", funds),
+            msg,
+        )
+    }
+
+    fn shares(vault: &TestVault, amount: u128) -> StdResult<Uint128> {
+        let deps = mock_dependencies();
+        vault.convert_to_shares(deps.as_ref(), mock_env(), amount.into())
+    }
+
+    fn assets(vault: &TestVault, amount: u128) -> StdResult<Uint128> {
+        let deps = mock_dependencies();
+        vault.convert_to_assets(deps.as_ref(), mock_env(), amount.into())
+    }
+
+    #[test]
+    fn deposit_and_redeem_are_rejected_while_paused() {
+        let vault = TestVault {
+            paused: true,
+            ..Default::default()
+        };
+        let deposit = VaultStandardExecuteMsg::Deposit {
+            amount: 100u128.into(),
+            recipient: None,
+        };
+        let redeem = VaultStandardExecuteMsg::Redeem {
+            amount: 100u128.into(),
+            recipient: None,
+        };
+        let err = execute_msg(&vault, deposit.clone(), &coins(100, BASE_TOKEN)).unwrap_err();
+        assert_eq!(
+            err,
+            TestError::Std(StdError::generic_err("vault is paused"))
+        );
+        let err = execute_msg(&vault, redeem.clone(), &coins(100, VAULT_TOKEN)).unwrap_err();
+        assert_eq!(
+            err,
+            TestError::Std(StdError::generic_err("vault is paused"))
+        );
+
+        let vault = TestVault::default();
+        execute_msg(&vault, deposit, &coins(100, BASE_TOKEN)).unwrap();
+        execute_msg(&vault, redeem, &coins(100, VAULT_TOKEN)).unwrap();
+    }
+
+    #[test]
+    fn first_deposit_mints_one_to_one() {
+        let vault = TestVault::default();
+        assert_eq!(shares(&vault, 1000).unwrap(), Uint128::new(1000));
+        assert_eq!(assets(&vault, 1000).unwrap(), Uint128::new(1000));
+    }
+
+    #[test]
+    fn conversions_follow_exchange_rate() {
+        let vault = TestVault {
+            total_assets: 1_999_999,
+            total_supply: 999_999,
+            ..Default::default()
+        };
+        assert_eq!(shares(&vault, 2000).unwrap(), Uint128::new(1000));
+        assert_eq!(assets(&vault, 1000).unwrap(), Uint128::new(2000));
+    }
+
+    #[test]
+    fn deposits_into_vault_without_assets_are_rejected() {
+        let vault = TestVault {
+            total_assets: 0,
+            total_supply: 1000,
+            ..Default::default()
+        };
+        shares(&vault, 1000).unwrap_err();
+    }
+
+    #[test]
+    fn donations_do_not_steal_first_deposit() {
+        // The attacker deposits 1 base token, minting 1000 vault tokens, and
+        // then donates 10_000 base tokens to the vault to inflate the exchange
+        // rate before the next deposit.
+        let vault = TestVault {
+            total_assets: 10_001,
+            total_supply: 1000,
+            decimals_offset: 3,
+            ..Default::default()
+        };
+        let minted = shares(&vault, 10_000).unwrap();
+        assert_eq!(minted, Uint128::new(1999));
+
+        // The depositor can still redeem almost all of their deposit
+        let vault = TestVault {
+            total_assets: 20_001,
+            total_supply: 1000 + minted.u128(),
+            decimals_offset: 3,
+            ..Default::default()
+        };
+        assert_eq!(assets(&vault, minted.u128()).unwrap(), Uint128::new(9998));
+    }
+
+    #[test]
+    fn decimals_offset_scales_first_deposit() {
+        let vault = TestVault {
+            decimals_offset: 6,
+            ..Default::default()
+        };
+        assert_eq!(shares(&vault, 5).unwrap(), Uint128::new(5_000_000));
+        assert_eq!(assets(&vault, 5_000_000).unwrap(), Uint128::new(5));
+    }
+
+    #[cfg(feature = "lockup")]
+    mod lockup {
+        use super::*;
+
+        use std::cell::RefCell;
+
+        use cw_utils::{Duration, Expiration};
+
+        use crate::extensions::lockup::ClaimableUnlockedResponse;
+
+        /// A call to the vault and its arguments.
+        #[derive(Debug, PartialEq)]
+        enum Call {
+            Unlock(Uint128, Option<u64>),
+            WithdrawUnlocked(u64, Addr),
+            Transfer(u64, Addr),
+            Merge(Vec<u64>),
+            #[cfg(feature = "lockup-nft")]
+            WithdrawReceived(u64, Addr, Addr),
+        }
+
+        #[derive(Default)]
+        struct LockupVault {
+            positions: Vec<UnlockingPosition>,
+            ignore_start_after: bool,
+            calls: RefCell<Vec<Call>>,
+        }
+
+        impl LockupVault {
+            /// A vault with `count` positions of `owner`, of which those with an
+            /// even ID have finished unlocking.
+            fn with_positions(owner: &str, count: u64) -> Self {
+                let block = mock_env().block;
+                let positions = (0..count)
+                    .map(|id| UnlockingPosition {
+                        id,
+                        owner: Addr::unchecked(owner),
+                        release_at: if id % 2 == 0 {
+                            Expiration::AtHeight(block.height)
+                        } else {
+                            Expiration::AtHeight(block.height + 1)
+                        },
+                        base_token_amount: Uint128::new(10),
+                        tier: None,
+                        vault_token_amount: Uint128::new(10),
+                        created_at: block.time,
+                    })
+                    .collect();
+                Self {
+                    positions,
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl VaultImpl for LockupVault {
+            type Error = TestError;
+
+            fn vault_info(&self, deps: Deps, env: Env) -> StdResult<VaultInfoResponse> {
+                TestVault::default().vault_info(deps, env)
+            }
+
+            fn deposit(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn redeem(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn total_assets(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+
+            fn total_vault_token_supply(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+        }
+
+        impl LockupImpl for LockupVault {
+            fn unlock(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                amount: Uint128,
+                tier: Option<u64>,
+            ) -> Result<Response, TestError> {
+                self.calls.borrow_mut().push(Call::Unlock(amount, tier));
+                Ok(Response::new())
+            }
+
+            fn emergency_unlock(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn withdraw_unlocked(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                lockup_id: u64,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.calls
+                    .borrow_mut()
+                    .push(Call::WithdrawUnlocked(lockup_id, recipient));
+                Ok(Response::new())
+            }
+
+            fn transfer_unlocking_position(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                lockup_id: u64,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.calls
+                    .borrow_mut()
+                    .push(Call::Transfer(lockup_id, recipient));
+                Ok(Response::new())
+            }
+
+            fn merge_unlocking_positions(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                ids: Vec<u64>,
+            ) -> Result<Response, TestError> {
+                self.calls.borrow_mut().push(Call::Merge(ids));
+                Ok(Response::new())
+            }
+
+            fn unlocking_positions(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                owner: Addr,
+                start_after: Option<u64>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<UnlockingPosition>> {
+                let start_after = start_after.filter(|_| !self.ignore_start_after);
+                Ok(self
+                    .positions
+                    .iter()
+                    .filter(|position| position.owner == owner)
+                    .filter(|position| start_after.map_or(true, |start| position.id > start))
+                    .take(limit.unwrap_or(10) as usize)
+                    .cloned()
+                    .collect())
+            }
+
+            fn unlocking_position(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                lockup_id: u64,
+            ) -> StdResult<UnlockingPosition> {
+                self.positions
+                    .iter()
+                    .find(|position| position.id == lockup_id)
+                    .cloned()
+                    .ok_or_else(|| StdError::not_found("unlocking position"))
+            }
+
+            fn lockup_duration(&self, _deps: Deps, _env: Env) -> StdResult<Duration> {
+                Ok(Duration::Time(100))
+            }
+        }
+
+        fn execute_msg(
+            vault: &LockupVault,
+            msg: LockupExecuteMsg,
+            funds: &[Coin],
+        ) -> Result<Response, TestError> {
+            let mut deps = mock_dependencies();
+            execute_lockup(
+                vault,
+                deps.as_mut(),
+                mock_env(),
+                mock_info("user", funds),
+                msg,
+            )
+        }
+
+        fn query_msg<T: serde::de::DeserializeOwned>(
+            vault: &LockupVault,
+            msg: LockupQueryMsg,
+        ) -> StdResult<T> {
+            let deps = mock_dependencies();
+            cosmwasm_std::from_json(query_lockup(vault, deps.as_ref(), mock_env(), msg)?)
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn unlock_reads_vault_tokens_from_funds() {
+            let vault = LockupVault::default();
+            let unlock = LockupExecuteMsg::Unlock {
+                amount: Uint128::new(1),
+                tier: Some(2),
+            };
+
+            execute_msg(&vault, unlock.clone(), &coins(100, VAULT_TOKEN)).unwrap();
+            assert_eq!(
+                execute_msg(&vault, unlock, &coins(100, BASE_TOKEN)).unwrap_err(),
+                TestError::Payment(PaymentError::MissingDenom(VAULT_TOKEN.to_string()))
+            );
+            assert_eq!(
+                *vault.calls.borrow(),
+                vec![Call::Unlock(Uint128::new(100), Some(2))]
+            );
+        }
+
+        #[test]
+        fn variants_without_vault_tokens_are_nonpayable() {
+            let vault = LockupVault::with_positions("user", 2);
+            let msgs = [
+                LockupExecuteMsg::WithdrawUnlocked {
+                    recipient: None,
+                    lockup_id: 0,
+                },
+                LockupExecuteMsg::WithdrawAllUnlocked { recipient: None },
+                LockupExecuteMsg::WithdrawUnlockedBatch {
+                    lockup_ids: vec![0],
+                    recipient: None,
+                },
+                LockupExecuteMsg::TransferUnlockingPosition {
+                    lockup_id: 0,
+                    recipient: "other".to_string(),
+                },
+                LockupExecuteMsg::SplitUnlockingPosition {
+                    lockup_id: 0,
+                    amount: Uint128::one(),
+                },
+                LockupExecuteMsg::MergeUnlockingPositions { ids: vec![0, 1] },
+                LockupExecuteMsg::CancelUnlock { lockup_id: 0 },
+                LockupExecuteMsg::EarlyWithdraw {
+                    lockup_id: 0,
+                    recipient: None,
+                },
+            ];
+            for msg in msgs {
+                assert_eq!(
+                    execute_msg(&vault, msg, &coins(1, BASE_TOKEN)).unwrap_err(),
+                    TestError::Payment(PaymentError::NonPayable {})
+                );
+            }
+            assert!(vault.calls.borrow().is_empty());
+        }
+
+        #[test]
+        fn recipients_default_to_sender_and_are_validated() {
+            let vault = LockupVault::with_positions("user", 1);
+            execute_msg(
+                &vault,
+                LockupExecuteMsg::WithdrawUnlocked {
+                    recipient: None,
+                    lockup_id: 0,
+                },
+                &[],
+            )
+            .unwrap();
+            execute_msg(
+                &vault,
+                LockupExecuteMsg::TransferUnlockingPosition {
+                    lockup_id: 0,
+                    recipient: "".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+            assert_eq!(
+                *vault.calls.borrow(),
+                vec![Call::WithdrawUnlocked(0, Addr::unchecked("user"))]
+            );
+        }
+
+        #[test]
+        fn merge_rejects_duplicate_ids() {
+            let vault = LockupVault::with_positions("user", 3);
+            let err = execute_msg(
+                &vault,
+                LockupExecuteMsg::MergeUnlockingPositions { ids: vec![0, 2, 0] },
+                &[],
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                TestError::Std(StdError::generic_err(
+                    "unlocking position 0 can not be merged more than once"
+                ))
+            );
+
+            execute_msg(
+                &vault,
+                LockupExecuteMsg::MergeUnlockingPositions { ids: vec![0, 2] },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(*vault.calls.borrow(), vec![Call::Merge(vec![0, 2])]);
+        }
+
+        #[test]
+        fn withdraw_all_unlocked_withdraws_claimable_positions_of_all_pages() {
+            let vault = LockupVault::with_positions("user", 25);
+            execute_msg(
+                &vault,
+                LockupExecuteMsg::WithdrawAllUnlocked {
+                    recipient: Some("recipient".to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+            let expected: Vec<_> = (0..25)
+                .step_by(2)
+                .map(|id| Call::WithdrawUnlocked(id, Addr::unchecked("recipient")))
+                .collect();
+            assert_eq!(*vault.calls.borrow(), expected);
+        }
+
+        #[test]
+        fn claimable_unlocked_stops_after_last_page() {
+            // Exactly two full pages
+            let vault = LockupVault::with_positions("user", 20);
+            let claimable: ClaimableUnlockedResponse = query_msg(
+                &vault,
+                LockupQueryMsg::ClaimableUnlocked {
+                    owner: "user".to_string(),
+                },
+            )
+            .unwrap();
+            assert_eq!(claimable.lockup_ids.len(), 10);
+            assert_eq!(claimable.base_token_amount, Uint128::new(100));
+        }
+
+        #[test]
+        fn claimable_unlocked_rejects_implementations_ignoring_start_after() {
+            let vault = LockupVault {
+                ignore_start_after: true,
+                ..LockupVault::with_positions("user", 20)
+            };
+            query_msg::<ClaimableUnlockedResponse>(
+                &vault,
+                LockupQueryMsg::ClaimableUnlocked {
+                    owner: "user".to_string(),
+                },
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn queries_are_dispatched() {
+            let vault = LockupVault::with_positions("user", 3);
+            let positions: Vec<UnlockingPosition> = query_msg(
+                &vault,
+                LockupQueryMsg::UnlockingPositions {
+                    owner: "user".to_string(),
+                    start_after: Some(0),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+            assert_eq!(positions, vec![vault.positions[1].clone()]);
+            assert_eq!(
+                query_msg::<Duration>(&vault, LockupQueryMsg::LockupDuration {}).unwrap(),
+                Duration::Time(100)
+            );
+            query_msg::<Vec<UnlockingPosition>>(
+                &vault,
+                LockupQueryMsg::UnlockingPositions {
+                    owner: "".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap_err();
+            query_msg::<Uint128>(&vault, LockupQueryMsg::TotalUnlocking {}).unwrap_err();
+        }
+
+        #[cfg(feature = "lockup-nft")]
+        mod lockup_nft {
+            use super::*;
+
+            use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+            use cosmwasm_std::{
+                from_json, OwnedDeps, QuerierResult, SystemError, SystemResult, WasmQuery,
+            };
+
+            use crate::extensions::lockup_nft::{
+                lockup_id_to_token_id, token_id_to_lockup_id, Approval, ApprovalResponse,
+                Cw721QueryMsg, OwnerOfResponse,
+            };
+
+            impl LockupNftImpl for LockupVault {
+                fn lockup_nft(&self, _deps: Deps, _env: Env) -> StdResult<LockupNft> {
+                    Ok(LockupNft::new(Addr::unchecked("nft")))
+                }
+
+                fn withdraw_received_unlocked(
+                    &self,
+                    _deps: DepsMut,
+                    _env: Env,
+                    lockup_id: u64,
+                    owner: Addr,
+                    recipient: Addr,
+                ) -> Result<Response, TestError> {
+                    self.calls
+                        .borrow_mut()
+                        .push(Call::WithdrawReceived(lockup_id, owner, recipient));
+                    Ok(Response::new())
+                }
+            }
+
+            /// A cw721 contract at "nft" in which "user" owns every token
+            /// except that of position 3, and the vault is approved for every
+            /// token except that of position 2.
+            fn cw721_querier(query: &WasmQuery) -> QuerierResult {
+                let WasmQuery::Smart { contract_addr, msg } = query else {
+                    panic!("unexpected query: {query:?}");
+                };
+                if contract_addr != "nft" {
+                    return SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    });
+                }
+                let res = match from_json(msg).unwrap() {
+                    Cw721QueryMsg::OwnerOf { token_id, .. } => {
+                        let owner = match token_id_to_lockup_id(&token_id).unwrap() {
+                            3 => "other",
+                            _ => "user",
+                        };
+                        to_json_binary(&OwnerOfResponse {
+                            owner: owner.to_string(),
+                            approvals: vec![],
+                        })
+                    }
+                    Cw721QueryMsg::Approval {
+                        token_id, spender, ..
+                    } if token_id != lockup_id_to_token_id(2) => {
+                        to_json_binary(&ApprovalResponse {
+                            approval: Approval {
+                                spender,
+                                expires: Expiration::Never {},
+                            },
+                        })
+                    }
+                    Cw721QueryMsg::Approval { .. } => Err(StdError::not_found("Approval")),
+                    Cw721QueryMsg::Tokens { .. } => unimplemented!(),
+                };
+                SystemResult::Ok(res.map_err(|err| err.to_string()).into())
+            }
+
+            fn nft_deps() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+                let mut deps = mock_dependencies();
+                deps.querier.update_wasm(cw721_querier);
+                deps
+            }
+
+            fn execute_msg(
+                vault: &LockupVault,
+                msg: LockupExecuteMsg,
+            ) -> Result<Response, TestError> {
+                let mut deps = nft_deps();
+                execute_lockup_nft(
+                    vault,
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info("user", &[]),
+                    msg,
+                )
+            }
+
+            fn receive_msg(
+                vault: &LockupVault,
+                sender: &str,
+                recipient: Option<String>,
+            ) -> Result<Response, TestError> {
+                let mut deps = nft_deps();
+                let msg = Cw721ReceiveMsg {
+                    sender: "user".to_string(),
+                    token_id: lockup_id_to_token_id(7),
+                    msg: to_json_binary(&LockupNftReceiveMsg::WithdrawUnlocked { recipient })
+                        .unwrap(),
+                };
+                receive_nft(
+                    vault,
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info(sender, &[]),
+                    msg,
+                )
+            }
+
+            #[test]
+            fn transfer_is_rejected() {
+                let vault = LockupVault::default();
+                execute_msg(
+                    &vault,
+                    LockupExecuteMsg::TransferUnlockingPosition {
+                        lockup_id: 0,
+                        recipient: "recipient".to_string(),
+                    },
+                )
+                .unwrap_err();
+                assert_eq!(*vault.calls.borrow(), vec![]);
+            }
+
+            #[test]
+            fn withdraw_checks_token_owner_and_approval() {
+                let vault = LockupVault::default();
+                let withdraw = |lockup_id| LockupExecuteMsg::WithdrawUnlocked {
+                    lockup_id,
+                    recipient: None,
+                };
+
+                execute_msg(&vault, withdraw(0)).unwrap();
+                let err = execute_msg(&vault, withdraw(3)).unwrap_err();
+                assert_eq!(
+                    err,
+                    TestError::Std(StdError::generic_err(
+                        "user does not own unlocking position 3"
+                    ))
+                );
+                let err = execute_msg(&vault, withdraw(2)).unwrap_err();
+                assert_eq!(
+                    err,
+                    TestError::Std(StdError::generic_err(
+                        "cosmos2contract is not approved for unlocking position 2"
+                    ))
+                );
+                execute_msg(
+                    &vault,
+                    LockupExecuteMsg::WithdrawUnlockedBatch {
+                        lockup_ids: vec![0, 2],
+                        recipient: None,
+                    },
+                )
+                .unwrap_err();
+
+                assert_eq!(
+                    *vault.calls.borrow(),
+                    vec![Call::WithdrawUnlocked(0, Addr::unchecked("user"))]
+                );
+            }
+
+            #[test]
+            fn merge_needs_approval_for_merged_positions_only() {
+                let vault = LockupVault::default();
+
+                execute_msg(
+                    &vault,
+                    LockupExecuteMsg::MergeUnlockingPositions { ids: vec![0, 2] },
+                )
+                .unwrap_err();
+                execute_msg(
+                    &vault,
+                    LockupExecuteMsg::MergeUnlockingPositions { ids: vec![0, 3] },
+                )
+                .unwrap_err();
+                execute_msg(
+                    &vault,
+                    LockupExecuteMsg::MergeUnlockingPositions { ids: vec![2, 0] },
+                )
+                .unwrap();
+
+                assert_eq!(*vault.calls.borrow(), vec![Call::Merge(vec![2, 0])]);
+            }
+
+            #[test]
+            fn received_tokens_are_withdrawn() {
+                let vault = LockupVault::default();
+                let user = Addr::unchecked("user");
+
+                receive_msg(&vault, "user", None).unwrap_err();
+                receive_msg(&vault, "nft", None).unwrap();
+                receive_msg(&vault, "nft", Some("recipient".to_string())).unwrap();
+
+                assert_eq!(
+                    *vault.calls.borrow(),
+                    vec![
+                        Call::WithdrawReceived(7, user.clone(), user.clone()),
+                        Call::WithdrawReceived(7, user, Addr::unchecked("recipient")),
+                    ]
+                );
+            }
+
+            #[test]
+            fn nft_contract_is_queried() {
+                let deps = mock_dependencies();
+                let res = query_lockup_nft(
+                    &LockupVault::default(),
+                    deps.as_ref(),
+                    mock_env(),
+                    LockupNftQueryMsg::NftContract {},
+                )
+                .unwrap();
+                assert_eq!(from_json::<Addr>(res).unwrap(), Addr::unchecked("nft"));
+            }
+        }
+    }
+
+    #[cfg(feature = "force-unlock")]
+    mod force_unlock {
+        use super::*;
+
+        use std::cell::RefCell;
+
+        /// A call to the vault and its arguments.
+        #[derive(Debug, PartialEq)]
+        enum Call {
+            ForceRedeem(Uint128, Addr),
+            ForceWithdrawUnlocking(u64, Option<Uint128>, Addr),
+            UpdateWhitelist(Vec<Addr>, Vec<Addr>),
+        }
+
+        #[derive(Default)]
+        struct ForceUnlockVault {
+            calls: RefCell<Vec<Call>>,
+        }
+
+        impl VaultImpl for ForceUnlockVault {
+            type Error = TestError;
+
+            fn vault_info(&self, deps: Deps, env: Env) -> StdResult<VaultInfoResponse> {
+                TestVault::default().vault_info(deps, env)
+            }
+
+            fn deposit(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn redeem(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn total_assets(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+
+            fn total_vault_token_supply(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+        }
+
+        impl ForceUnlockImpl for ForceUnlockVault {
+            fn force_redeem(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                amount: Uint128,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.calls
+                    .borrow_mut()
+                    .push(Call::ForceRedeem(amount, recipient));
+                Ok(Response::new())
+            }
+
+            fn force_withdraw_unlocking(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                lockup_id: u64,
+                amount: Option<Uint128>,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.calls
+                    .borrow_mut()
+                    .push(Call::ForceWithdrawUnlocking(lockup_id, amount, recipient));
+                Ok(Response::new())
+            }
+
+            fn update_force_withdraw_whitelist(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                add_addresses: Vec<Addr>,
+                remove_addresses: Vec<Addr>,
+            ) -> Result<Response, TestError> {
+                self.calls
+                    .borrow_mut()
+                    .push(Call::UpdateWhitelist(add_addresses, remove_addresses));
+                Ok(Response::new())
+            }
+        }
+
+        fn execute_msg(
+            vault: &ForceUnlockVault,
+            msg: ForceUnlockExecuteMsg,
+            funds: &[Coin],
+        ) -> Result<Response, TestError> {
+            let mut deps = mock_dependencies();
+            execute_force_unlock(
+                vault,
+                deps.as_mut(),
+                mock_env(),
+                mock_info("user", funds),
+                msg,
+            )
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn force_redeem_reads_vault_tokens_from_funds() {
+            let vault = ForceUnlockVault::default();
+            let msg = ForceUnlockExecuteMsg::ForceRedeem {
+                recipient: None,
+                amount: Uint128::one(),
+            };
+            execute_msg(&vault, msg.clone(), &coins(100, VAULT_TOKEN)).unwrap();
+            assert_eq!(
+                execute_msg(&vault, msg, &[]).unwrap_err(),
+                TestError::Payment(PaymentError::NoFunds {})
+            );
+            assert_eq!(
+                *vault.calls.borrow(),
+                vec![Call::ForceRedeem(
+                    Uint128::new(100),
+                    Addr::unchecked("user")
+                )]
+            );
+        }
+
+        #[test]
+        fn force_withdraw_unlocking_is_nonpayable() {
+            let vault = ForceUnlockVault::default();
+            let msg = ForceUnlockExecuteMsg::ForceWithdrawUnlocking {
+                lockup_id: 1,
+                amount: None,
+                recipient: Some("recipient".to_string()),
+            };
+            assert_eq!(
+                execute_msg(&vault, msg.clone(), &coins(1, VAULT_TOKEN)).unwrap_err(),
+                TestError::Payment(PaymentError::NonPayable {})
+            );
+            execute_msg(&vault, msg, &[]).unwrap();
+            assert_eq!(
+                *vault.calls.borrow(),
+                vec![Call::ForceWithdrawUnlocking(
+                    1,
+                    None,
+                    Addr::unchecked("recipient")
+                )]
+            );
+        }
+
+        #[test]
+        fn whitelist_updates_are_validated() {
+            let vault = ForceUnlockVault::default();
+            let update = |add: &str| ForceUnlockExecuteMsg::UpdateForceWithdrawWhitelist {
+                add_addresses: vec![add.to_string()],
+                remove_addresses: vec!["removed".to_string()],
+            };
+            execute_msg(&vault, update(""), &[]).unwrap_err();
+            assert_eq!(
+                execute_msg(&vault, update("added"), &coins(1, BASE_TOKEN)).unwrap_err(),
+                TestError::Payment(PaymentError::NonPayable {})
+            );
+            execute_msg(&vault, update("added"), &[]).unwrap();
+            assert_eq!(
+                *vault.calls.borrow(),
+                vec![Call::UpdateWhitelist(
+                    vec![Addr::unchecked("added")],
+                    vec![Addr::unchecked("removed")]
+                )]
+            );
+        }
+    }
+
+    #[cfg(feature = "keeper")]
+    mod keeper {
+        use super::*;
+
+        use std::cell::RefCell;
+
+        use cw_utils::{Duration, Expiration};
+
+        use cosmwasm_std::coin;
+
+        use crate::extensions::keeper::{KeeperBounty, KeeperReward};
+
+        /// A bounty paid to a keeper for a job at an execution.
+        type Payment = (u64, Addr, Vec<Coin>, KeeperJobExecution);
+
+        #[derive(Default)]
+        struct KeeperVault {
+            jobs: RefCell<Vec<KeeperJob>>,
+            executions: RefCell<Vec<u64>>,
+            whitelist: RefCell<Vec<(Option<u64>, Addr)>>,
+            harvest: Vec<Coin>,
+            payments: RefCell<Vec<Payment>>,
+        }
+
+        impl KeeperVault {
+            fn with_jobs(jobs: Vec<KeeperJob>) -> Self {
+                Self {
+                    jobs: RefCell::new(jobs),
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl VaultImpl for KeeperVault {
+            type Error = TestError;
+
+            fn vault_info(&self, deps: Deps, env: Env) -> StdResult<VaultInfoResponse> {
+                TestVault::default().vault_info(deps, env)
+            }
+
+            fn deposit(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn redeem(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _amount: Uint128,
+                _recipient: Addr,
+            ) -> Result<Response, TestError> {
+                Ok(Response::new())
+            }
+
+            fn total_assets(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+
+            fn total_vault_token_supply(&self, _deps: Deps, _env: Env) -> StdResult<Uint128> {
+                Ok(Uint128::zero())
+            }
+        }
+
+        impl KeeperImpl for KeeperVault {
+            fn whitelist_keeper(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                job_id: Option<u64>,
+                keeper: Addr,
+            ) -> Result<Response, TestError> {
+                self.whitelist.borrow_mut().push((job_id, keeper));
+                Ok(Response::new())
+            }
+
+            fn blacklist_keeper(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                job_id: Option<u64>,
+                keeper: Addr,
+            ) -> Result<Response, TestError> {
+                self.whitelist
+                    .borrow_mut()
+                    .retain(|entry| *entry != (job_id, keeper.clone()));
+                Ok(Response::new())
+            }
+
+            fn keeper_whitelist_contains(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                job_id: Option<u64>,
+                keeper: &Addr,
+            ) -> StdResult<bool> {
+                Ok(self.whitelist.borrow().contains(&(job_id, keeper.clone())))
+            }
+
+            fn whitelisted_keepers(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                job_id: Option<u64>,
+                start_after: Option<Addr>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<Addr>> {
+                let mut keepers: Vec<_> = self
+                    .whitelist
+                    .borrow()
+                    .iter()
+                    .filter(|(id, keeper)| {
+                        *id == job_id && start_after.as_ref().map_or(true, |start| keeper > start)
+                    })
+                    .map(|(_, keeper)| keeper.clone())
+                    .collect();
+                keepers.sort();
+                keepers.truncate(limit.unwrap_or(10) as usize);
+                Ok(keepers)
+            }
+
+            fn execute_job(
+                &self,
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                job_id: u64,
+            ) -> Result<Response, TestError> {
+                self.executions.borrow_mut().push(job_id);
+                Ok(Response::new())
+            }
+
+            fn save_keeper_job(&self, _deps: DepsMut, _env: Env, job: &KeeperJob) -> StdResult<()> {
+                let mut jobs = self.jobs.borrow_mut();
+                match jobs.iter_mut().find(|stored| stored.id == job.id) {
+                    Some(stored) => *stored = job.clone(),
+                    None => jobs.push(job.clone()),
+                }
+                Ok(())
+            }
+
+            fn keeper_jobs(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                start_after: Option<u64>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<KeeperJob>> {
+                Ok(self
+                    .jobs
+                    .borrow()
+                    .iter()
+                    .filter(|job| start_after.map_or(true, |start_after| job.id > start_after))
+                    .take(limit.unwrap_or(10) as usize)
+                    .cloned()
+                    .collect())
+            }
+
+            fn keeper_job_ready(&self, deps: Deps, env: Env, job_id: u64) -> StdResult<bool> {
+                Ok(self
+                    .keeper_job(deps, env.clone(), job_id)?
+                    .is_due(&env.block))
+            }
+
+            fn keeper_last_paid(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                job_id: u64,
+                keeper: &Addr,
+            ) -> StdResult<Option<KeeperJobExecution>> {
+                Ok(self
+                    .payments
+                    .borrow()
+                    .iter()
+                    .rev()
+                    .find(|(id, paid, ..)| *id == job_id && paid == keeper)
+                    .map(|(.., execution)| execution.clone()))
+            }
+
+            fn harvested_yield(
+                &self,
+                _deps: Deps,
+                _env: Env,
+                _job: &KeeperJob,
+            ) -> StdResult<Vec<Coin>> {
+                Ok(self.harvest.clone())
+            }
+
+            fn accrue_keeper_rewards(
+                &self,
+                _deps: DepsMut,
+                env: Env,
+                job_id: u64,
+                keeper: &Addr,
+                rewards: &[Coin],
+            ) -> StdResult<bool> {
+                self.payments.borrow_mut().push((
+                    job_id,
+                    keeper.clone(),
+                    rewards.to_vec(),
+                    KeeperJobExecution {
+                        height: env.block.height,
+                        time: env.block.time,
+                    },
+                ));
+                Ok(true)
+            }
+        }
+
+        fn execute_job(
+            vault: &KeeperVault,
+            env: Env,
+            keeper: &str,
+            job_id: u64,
+        ) -> Result<Response, TestError> {
+            let mut deps = mock_dependencies();
+            execute_keeper(
+                vault,
+                deps.as_mut(),
+                env,
+                mock_info(keeper, &[]),
+                KeeperExecuteMsg::ExecuteJob { job_id },
+            )
+        }
+
+        fn job(vault: &KeeperVault, job_id: u64) -> KeeperJob {
+            let deps = mock_dependencies();
+            vault.keeper_job(deps.as_ref(), mock_env(), job_id).unwrap()
+        }
+
+        fn env_after(seconds: u64) -> Env {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env.block.height += seconds / 5;
+            env
+        }
+
+        #[test]
+        fn execute_job_records_execution() {
+            let vault = KeeperVault::with_jobs(vec![KeeperJob {
+                interval: Some(Duration::Time(100)),
+                ..KeeperJob::new(0, false)
+            }]);
+            let env = mock_env();
+
+            execute_job(&vault, env.clone(), "keeper", 0).unwrap();
+            let job = job(&vault, 0);
+            assert_eq!(
+                job.last_executed,
+                Some(KeeperJobExecution {
+                    height: env.block.height,
+                    time: env.block.time,
+                })
+            );
+            assert_eq!(
+                job.next_executable_at,
+                Some(Expiration::AtTime(env.block.time.plus_seconds(100)))
+            );
+
+            // The job can not be executed again until its interval has passed
+            let err = execute_job(&vault, env_after(99), "keeper", 0).unwrap_err();
+            assert_eq!(
+                err,
+                TestError::Std(StdError::generic_err("keeper job 0 is not ready"))
+            );
+            execute_job(&vault, env_after(100), "keeper", 0).unwrap();
+            assert_eq!(*vault.executions.borrow(), vec![0, 0]);
+            assert_eq!(
+                self::job(&vault, 0).last_executed.map(|last| last.time),
+                Some(env.block.time.plus_seconds(100))
+            );
+        }
+
+        fn update_whitelist(
+            vault: &KeeperVault,
+            job_id: Option<u64>,
+            add: &[&str],
+            remove: &[&str],
+        ) -> Result<Response, TestError> {
+            let mut deps = mock_dependencies();
+            let to_strings = |keepers: &[&str]| keepers.iter().map(|k| k.to_string()).collect();
+            execute_keeper(
+                vault,
+                deps.as_mut(),
+                mock_env(),
+                mock_info("admin", &[]),
+                KeeperExecuteMsg::UpdateKeeperWhitelist {
+                    job_id,
+                    add: to_strings(add),
+                    remove: to_strings(remove),
+                },
+            )
+        }
+
+        fn whitelisted(vault: &KeeperVault, job_id: Option<u64>) -> Vec<Addr> {
+            let deps = mock_dependencies();
+            vault
+                .whitelisted_keepers(deps.as_ref(), mock_env(), job_id, None, None)
+                .unwrap()
+        }
+
+        #[test]
+        fn keeper_job_finds_jobs_by_id() {
+            let vault =
+                KeeperVault::with_jobs((0..15).map(|id| KeeperJob::new(id, true)).collect());
+            assert_eq!(job(&vault, 0).id, 0);
+            assert_eq!(job(&vault, 14).id, 14);
+            let deps = mock_dependencies();
+            vault.keeper_job(deps.as_ref(), mock_env(), 15).unwrap_err();
+        }
+
+        #[test]
+        fn execute_job_checks_whitelist() {
+            let vault = KeeperVault::with_jobs(vec![KeeperJob::new(0, true)]);
+            update_whitelist(&vault, Some(0), &["keeper"], &[]).unwrap();
+
+            execute_job(&vault, mock_env(), "other", 0).unwrap_err();
+            assert_eq!(job(&vault, 0).last_executed, None);
+
+            execute_job(&vault, mock_env(), "keeper", 0).unwrap();
+            assert_eq!(
+                job(&vault, 0).last_executed.map(|last| last.time),
+                Some(mock_env().block.time)
+            );
+        }
+
+        #[test]
+        fn globally_whitelisted_keepers_can_execute_all_jobs() {
+            let vault =
+                KeeperVault::with_jobs(vec![KeeperJob::new(0, true), KeeperJob::new(1, true)]);
+            update_whitelist(&vault, None, &["global", "other"], &["other"]).unwrap();
+            assert_eq!(whitelisted(&vault, None), vec![Addr::unchecked("global")]);
+            assert!(whitelisted(&vault, Some(0)).is_empty());
+
+            execute_job(&vault, mock_env(), "global", 0).unwrap();
+            execute_job(&vault, mock_env(), "global", 1).unwrap();
+            execute_job(&vault, mock_env(), "other", 1).unwrap_err();
+
+            update_whitelist(&vault, None, &[], &["global"]).unwrap();
+            execute_job(&vault, env_after(1), "global", 0).unwrap_err();
+        }
+
+        fn bounty_job(reward: KeeperReward) -> KeeperJob {
+            KeeperJob {
+                bounty: Some(KeeperBounty {
+                    reward,
+                    min_interval: Some(Duration::Time(100)),
+                    cooldown: Some(Duration::Time(300)),
+                }),
+                ..KeeperJob::new(0, false)
+            }
+        }
+
+        fn paid_rewards(vault: &KeeperVault) -> Vec<(String, Vec<Coin>)> {
+            vault
+                .payments
+                .borrow()
+                .iter()
+                .map(|(_, keeper, rewards, _)| (keeper.to_string(), rewards.clone()))
+                .collect()
+        }
+
+        #[test]
+        fn execute_job_accrues_payable_bounty() {
+            let vault = KeeperVault {
+                harvest: vec![coin(1000, "uatom"), coin(5, "uosmo")],
+                ..KeeperVault::with_jobs(vec![bounty_job(KeeperReward::YieldShare {
+                    share: Decimal::percent(1),
+                })])
+            };
+            let paid = |keeper: &str| (keeper.to_string(), coins(10, "uatom"));
+
+            let res = execute_job(&vault, mock_env(), "alice", 0).unwrap();
+            assert_eq!(paid_rewards(&vault), vec![paid("alice")]);
+            assert_eq!(
+                res.events,
+                vec![Event::new(KEEPER_BOUNTY_PAID_EVENT_TYPE)
+                    .add_attribute(JOB_ID_ATTR_KEY, "0")
+                    .add_attribute(KEEPER_ATTR_KEY, "alice")
+                    .add_attribute(REWARDS_ATTR_KEY, "10uatom")]
+            );
+
+            // Not payable before the min interval has passed since the last
+            // execution, but the job is still executed
+            let res = execute_job(&vault, env_after(50), "bob", 0).unwrap();
+            assert!(res.events.is_empty());
+            assert_eq!(*vault.executions.borrow(), vec![0, 0]);
+
+            // Payable to another keeper after the min interval, but not to a
+            // keeper in its cooldown
+            execute_job(&vault, env_after(150), "bob", 0).unwrap();
+            execute_job(&vault, env_after(250), "alice", 0).unwrap();
+            execute_job(&vault, env_after(350), "alice", 0).unwrap();
+            assert_eq!(
+                paid_rewards(&vault),
+                vec![paid("alice"), paid("bob"), paid("alice")]
+            );
+        }
+
+        #[test]
+        fn execute_job_without_payout_accrues_nothing() {
+            let vault = KeeperVault::with_jobs(vec![bounty_job(KeeperReward::YieldShare {
+                share: Decimal::percent(1),
+            })]);
+            let res = execute_job(&vault, mock_env(), "alice", 0).unwrap();
+            assert!(res.events.is_empty());
+            assert!(vault.payments.borrow().is_empty());
+        }
+
+        /// A vault that keeps the default `accrue_keeper_rewards`.
+        #[derive(Default)]
+        struct NoBountyVault(KeeperVault);
+
+        impl VaultImpl for NoBountyVault {
+            type Error = TestError;
+
+            fn vault_info(&self, deps: Deps, env: Env) -> StdResult<VaultInfoResponse> {
+                self.0.vault_info(deps, env)
+            }
+
+            fn deposit(
+                &self,
+                deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                amount: Uint128,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.0.deposit(deps, env, info, amount, recipient)
+            }
+
+            fn redeem(
+                &self,
+                deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                amount: Uint128,
+                recipient: Addr,
+            ) -> Result<Response, TestError> {
+                self.0.redeem(deps, env, info, amount, recipient)
+            }
+
+            fn total_assets(&self, deps: Deps, env: Env) -> StdResult<Uint128> {
+                self.0.total_assets(deps, env)
+            }
+
+            fn total_vault_token_supply(&self, deps: Deps, env: Env) -> StdResult<Uint128> {
+                self.0.total_vault_token_supply(deps, env)
+            }
+        }
+
+        impl KeeperImpl for NoBountyVault {
+            fn whitelist_keeper(
+                &self,
+                deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                job_id: Option<u64>,
+                keeper: Addr,
+            ) -> Result<Response, TestError> {
+                self.0.whitelist_keeper(deps, env, info, job_id, keeper)
+            }
+
+            fn blacklist_keeper(
+                &self,
+                deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                job_id: Option<u64>,
+                keeper: Addr,
+            ) -> Result<Response, TestError> {
+                self.0.blacklist_keeper(deps, env, info, job_id, keeper)
+            }
+
+            fn keeper_whitelist_contains(
+                &self,
+                deps: Deps,
+                env: Env,
+                job_id: Option<u64>,
+                keeper: &Addr,
+            ) -> StdResult<bool> {
+                self.0.keeper_whitelist_contains(deps, env, job_id, keeper)
+            }
+
+            fn whitelisted_keepers(
+                &self,
+                deps: Deps,
+                env: Env,
+                job_id: Option<u64>,
+                start_after: Option<Addr>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<Addr>> {
+                self.0
+                    .whitelisted_keepers(deps, env, job_id, start_after, limit)
+            }
+
+            fn execute_job(
+                &self,
+                deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                job_id: u64,
+            ) -> Result<Response, TestError> {
+                self.0.execute_job(deps, env, info, job_id)
+            }
+
+            fn save_keeper_job(&self, deps: DepsMut, env: Env, job: &KeeperJob) -> StdResult<()> {
+                self.0.save_keeper_job(deps, env, job)
+            }
+
+            fn keeper_jobs(
+                &self,
+                deps: Deps,
+                env: Env,
+                start_after: Option<u64>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<KeeperJob>> {
+                self.0.keeper_jobs(deps, env, start_after, limit)
+            }
+
+            fn keeper_job_ready(&self, deps: Deps, env: Env, job_id: u64) -> StdResult<bool> {
+                self.0.keeper_job_ready(deps, env, job_id)
+            }
+        }
+
+        #[test]
+        fn execute_job_skips_bounty_by_default() {
+            let vault = NoBountyVault(KeeperVault::with_jobs(vec![bounty_job(
+                KeeperReward::Fixed {
+                    amount: coin(10, "uosmo"),
+                },
+            )]));
+            let mut deps = mock_dependencies();
+            let res = execute_keeper(
+                &vault,
+                deps.as_mut(),
+                mock_env(),
+                mock_info("alice", &[]),
+                KeeperExecuteMsg::ExecuteJob { job_id: 0 },
+            )
+            .unwrap();
+            assert!(res.events.is_empty());
+            assert_eq!(*vault.0.executions.borrow(), vec![0]);
+            assert!(vault.0.payments.borrow().is_empty());
+        }
+
+        #[test]
+        fn add_keeper_job_rejects_yield_share_above_one() {
+            let vault = KeeperVault::default();
+            let mut deps = mock_dependencies();
+            let config = KeeperJobConfig {
+                whitelist: false,
+                name: None,
+                description: None,
+                interval: None,
+                params: None,
+                bounty: bounty_job(KeeperReward::YieldShare {
+                    share: Decimal::percent(101),
+                })
+                .bounty,
+            };
+            let err = execute_keeper(
+                &vault,
+                deps.as_mut(),
+                mock_env(),
+                mock_info("admin", &[]),
+                KeeperExecuteMsg::AddKeeperJob { config },
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                TestError::Std(StdError::generic_err(
+                    "keeper yield share 1.01 is more than one"
+                ))
+            );
+        }
+    }
+}
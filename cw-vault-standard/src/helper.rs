@@ -2,16 +2,30 @@ use std::marker::PhantomData;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    coin, to_json_binary, Addr, CosmosMsg, Decimal, Deps, QuerierWrapper, StdResult, Uint128,
-    WasmMsg,
+    coin, to_json_binary, Addr, Coin, CosmosMsg, Decimal, Deps, QuerierWrapper, StdError,
+    StdResult, Uint128, WasmMsg,
 };
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::compatibility::{
+    check_compatibility, Compatibility, MIN_COMPATIBLE_VERSION, NEWEST_SUPPORTED_VERSION,
+};
+#[cfg(feature = "force-unlock")]
+use crate::extensions::force_unlock::ForceUnlockExecuteMsg;
+#[cfg(feature = "keeper")]
+use crate::extensions::keeper::{KeeperExecuteMsg, KeeperJob, KeeperJobStatus, KeeperQueryMsg};
+#[cfg(feature = "lockup")]
+use crate::extensions::lockup::{LockupExecuteMsg, LockupQueryMsg, UnlockingPosition};
 use crate::{
-    ExtensionExecuteMsg, ExtensionQueryMsg, VaultInfoResponse, VaultStandardExecuteMsg,
-    VaultStandardInfoResponse, VaultStandardQueryMsg,
+    ExtensionExecuteMsg, ExtensionQueryMsg, IntoExtension, UserPositionResponse, VaultInfoResponse,
+    VaultStandardExecuteMsg, VaultStandardInfoResponse, VaultStandardQueryMsg, VaultStateResponse,
 };
+#[cfg(feature = "lockup")]
+use cosmwasm_std::Timestamp;
+#[cfg(feature = "lockup")]
+use cw_utils::Duration;
 
 /// A helper struct to interact with a vault contract that adheres to the vault
 /// standard. This struct contains an unchecked address. By calling the `check`
@@ -67,6 +81,11 @@ where
     Q: Serialize + JsonSchema,
 {
     /// Create a new VaultContract instance.
+    ///
+    /// Does not check the version of the vault standard used by the vault, so
+    /// that it keeps working for vaults that do not support the
+    /// `VaultStandardInfo` query. Use [`VaultContract::new_compatible`] to
+    /// refuse vaults using an incompatible version.
     pub fn new(querier: &QuerierWrapper, addr: &Addr) -> StdResult<Self> {
         // Query vault info
         let vault_info: VaultInfoResponse =
@@ -81,6 +100,22 @@ where
         })
     }
 
+    /// Create a new VaultContract instance, returning an error if the vault
+    /// uses a version of the vault standard that is not supported by this
+    /// crate, see [`check_compatibility`].
+    pub fn new_compatible(querier: &QuerierWrapper, addr: &Addr) -> StdResult<Self> {
+        let vault = Self::new(querier, addr)?;
+        let version = vault.query_vault_standard_info(querier)?.version;
+        if check_compatibility(&version)? == Compatibility::Breaking {
+            return Err(StdError::generic_err(format!(
+                "vault {addr} uses vault standard version {version}, which is not supported by \
+                 this crate (supported versions: {MIN_COMPATIBLE_VERSION} up to the \
+                 {NEWEST_SUPPORTED_VERSION} release line)"
+            )));
+        }
+        Ok(vault)
+    }
+
     /// Returns a CosmosMsg to deposit base tokens into the vault.
     pub fn deposit(
         &self,
@@ -141,6 +176,26 @@ where
         )
     }
 
+    /// Queries the vault for the vault standard info and returns an error if
+    /// the vault does not support all of the given extensions. Each extension
+    /// is given as a tuple of its name and the minimum required version.
+    pub fn require_extensions(
+        &self,
+        querier: &QuerierWrapper,
+        extensions: &[(&str, &str)],
+    ) -> StdResult<VaultStandardInfoResponse> {
+        let info = self.query_vault_standard_info(querier)?;
+        for (name, min_version) in extensions {
+            if !info.supports(name, min_version) {
+                return Err(StdError::generic_err(format!(
+                    "vault {} does not support extension {} with version {} or later",
+                    self.addr, name, min_version
+                )));
+            }
+        }
+        Ok(info)
+    }
+
     /// Queries the vault for the vault info
     pub fn query_vault_info(&self, querier: &QuerierWrapper) -> StdResult<VaultInfoResponse> {
         querier.query_wasm_smart(&self.addr, &VaultStandardQueryMsg::<Q>::Info {})
@@ -234,4 +289,486 @@ where
             },
         )
     }
+
+    /// Returns a CosmosMsg to execute an extension message on the vault.
+    pub fn execute_extension(
+        &self,
+        msg: impl IntoExtension<E>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr.to_string(),
+            msg: to_json_binary(&VaultStandardExecuteMsg::VaultExtension(
+                msg.into_extension(),
+            ))?,
+            funds,
+        }
+        .into())
+    }
+
+    /// Queries the vault with an extension query message.
+    pub fn query_extension<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: impl IntoExtension<Q>,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(
+            &self.addr,
+            &VaultStandardQueryMsg::VaultExtension(msg.into_extension()),
+        )
+    }
+
+    /// Queries the vault for the position of a user
+    pub fn query_user_position(
+        &self,
+        querier: &QuerierWrapper,
+        address: impl Into<String>,
+    ) -> StdResult<UserPositionResponse> {
+        querier.query_wasm_smart(
+            &self.addr,
+            &VaultStandardQueryMsg::<Q>::UserPosition {
+                address: address.into(),
+            },
+        )
+    }
+
+    /// Queries the vault for its aggregated state. Vaults that do not support
+    /// the `VaultState` query, such as vaults using older versions of the vault
+    /// standard, are queried with the individual queries instead. Since those
+    /// vaults can not report whether they are paused, `paused` is `false` in
+    /// that case. Errors other than the query not being supported are
+    /// returned as is.
+    pub fn query_state(&self, querier: &QuerierWrapper) -> StdResult<VaultStateResponse> {
+        match querier.query_wasm_smart(&self.addr, &VaultStandardQueryMsg::<Q>::VaultState {}) {
+            Ok(state) => return Ok(state),
+            Err(err) if !is_unsupported_query(&err) => return Err(err),
+            Err(_) => {}
+        }
+
+        let total_assets = self.query_total_assets(querier)?;
+        let total_vault_token_supply = self.query_total_vault_token_supply(querier)?;
+        let vault_token_exchange_rate =
+            match self.query_vault_token_exchange_rate(self.base_token.clone(), querier) {
+                Ok(rate) => rate,
+                // VaultTokenExchangeRate was added in version 0.4.1. Matches the
+                // exchange rate returned by the framework.
+                Err(err) if is_unsupported_query(&err) => {
+                    if total_vault_token_supply.is_zero() {
+                        Decimal::one()
+                    } else {
+                        Decimal::checked_from_ratio(total_assets, total_vault_token_supply)
+                            .map_err(|e| StdError::generic_err(e.to_string()))?
+                    }
+                }
+                Err(err) => return Err(err),
+            };
+        let version = self.query_vault_standard_info(querier)?.version;
+
+        Ok(VaultStateResponse {
+            base_token: self.base_token.clone(),
+            vault_token: self.vault_token.clone(),
+            total_assets,
+            total_vault_token_supply,
+            vault_token_exchange_rate,
+            paused: false,
+            version,
+        })
+    }
+}
+
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+impl<E, Q> VaultContract<E, Q>
+where
+    E: Serialize + From<LockupExecuteMsg>,
+    Q: Serialize + JsonSchema,
+{
+    /// Returns a CosmosMsg to unlock vault tokens, creating an unlocking
+    /// position.
+    pub fn unlock(&self, amount: impl Into<Uint128>) -> StdResult<CosmosMsg> {
+        self.unlock_in_tier(amount, None)
+    }
+
+    /// Returns a CosmosMsg to unlock vault tokens, creating an unlocking
+    /// position in lockup tier `tier`.
+    pub fn unlock_in_tier(
+        &self,
+        amount: impl Into<Uint128>,
+        tier: Option<u64>,
+    ) -> StdResult<CosmosMsg> {
+        let amount = amount.into();
+        #[allow(deprecated)]
+        let msg = LockupExecuteMsg::Unlock { amount, tier };
+        self.execute_extension(msg, vec![coin(amount.u128(), &self.vault_token)])
+    }
+
+    /// Returns a CosmosMsg to withdraw an unlocking position that has finished
+    /// unlocking.
+    pub fn withdraw_unlocked(
+        &self,
+        lockup_id: u64,
+        recipient: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.execute_extension(
+            LockupExecuteMsg::WithdrawUnlocked {
+                recipient,
+                lockup_id,
+            },
+            vec![],
+        )
+    }
+}
+
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+impl<E, Q> VaultContract<E, Q>
+where
+    E: Serialize,
+    Q: Serialize + JsonSchema + From<LockupQueryMsg>,
+{
+    /// Queries the vault for the unlocking positions of `owner`
+    pub fn query_unlocking_positions(
+        &self,
+        querier: &QuerierWrapper,
+        owner: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.query_extension(
+            querier,
+            LockupQueryMsg::UnlockingPositions {
+                owner: owner.into(),
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    /// Queries the vault for a single unlocking position
+    pub fn query_unlocking_position(
+        &self,
+        querier: &QuerierWrapper,
+        lockup_id: u64,
+    ) -> StdResult<UnlockingPosition> {
+        self.query_extension(querier, LockupQueryMsg::UnlockingPosition { lockup_id })
+    }
+
+    /// Queries the vault for its lockup duration
+    pub fn query_lockup_duration(&self, querier: &QuerierWrapper) -> StdResult<Duration> {
+        self.query_extension(querier, LockupQueryMsg::LockupDuration {})
+    }
+
+    /// Queries the vault for the unlocking positions of all owners
+    pub fn query_all_unlocking_positions(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.query_extension(
+            querier,
+            LockupQueryMsg::AllUnlockingPositions { start_after, limit },
+        )
+    }
+
+    /// Queries the vault for the unlocking positions that finish unlocking at
+    /// or before `time`
+    pub fn query_unlocking_positions_maturing_before(
+        &self,
+        querier: &QuerierWrapper,
+        time: Timestamp,
+        start_after: Option<(Timestamp, u64)>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.query_extension(
+            querier,
+            LockupQueryMsg::UnlockingPositionsMaturingBefore {
+                time,
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    /// Queries the vault for the sum of the base tokens of all unlocking
+    /// positions
+    pub fn query_total_unlocking(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        self.query_extension(querier, LockupQueryMsg::TotalUnlocking {})
+    }
+}
+
+#[cfg(feature = "force-unlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "force-unlock")))]
+impl<E, Q> VaultContract<E, Q>
+where
+    E: Serialize + From<ForceUnlockExecuteMsg>,
+    Q: Serialize + JsonSchema,
+{
+    /// Returns a CosmosMsg to force redeem vault tokens, bypassing the lockup.
+    /// Can only be called by whitelisted addresses.
+    pub fn force_redeem(
+        &self,
+        amount: impl Into<Uint128>,
+        recipient: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        let amount = amount.into();
+        #[allow(deprecated)]
+        let msg = ForceUnlockExecuteMsg::ForceRedeem { recipient, amount };
+        self.execute_extension(msg, vec![coin(amount.u128(), &self.vault_token)])
+    }
+
+    /// Returns a CosmosMsg to force withdraw `amount` base tokens, or the
+    /// entire position if `None`, from an unlocking position. Can only be
+    /// called by whitelisted addresses.
+    pub fn force_withdraw_unlocking(
+        &self,
+        lockup_id: u64,
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.execute_extension(
+            ForceUnlockExecuteMsg::ForceWithdrawUnlocking {
+                lockup_id,
+                amount,
+                recipient,
+            },
+            vec![],
+        )
+    }
+}
+
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+impl<E, Q> VaultContract<E, Q>
+where
+    E: Serialize + From<KeeperExecuteMsg>,
+    Q: Serialize + JsonSchema,
+{
+    /// Returns a CosmosMsg to execute a keeper job.
+    pub fn execute_keeper_job(&self, job_id: u64) -> StdResult<CosmosMsg> {
+        self.execute_extension(KeeperExecuteMsg::ExecuteJob { job_id }, vec![])
+    }
+
+    /// Returns a CosmosMsg to claim the keeper rewards accrued by the caller.
+    pub fn claim_keeper_rewards(&self, recipient: Option<String>) -> StdResult<CosmosMsg> {
+        self.execute_extension(KeeperExecuteMsg::ClaimKeeperRewards { recipient }, vec![])
+    }
+}
+
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+impl<E, Q> VaultContract<E, Q>
+where
+    E: Serialize,
+    Q: Serialize + JsonSchema + From<KeeperQueryMsg>,
+{
+    /// Queries the vault for its keeper jobs (with optional pagination)
+    pub fn query_keeper_jobs(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<KeeperJob>> {
+        self.query_extension(querier, KeeperQueryMsg::KeeperJobs { start_after, limit })
+    }
+
+    /// Queries the vault for the whitelisted keepers of a job, or the global
+    /// keeper whitelist if `job_id` is `None` (with optional pagination)
+    pub fn query_whitelisted_keepers(
+        &self,
+        querier: &QuerierWrapper,
+        job_id: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        self.query_extension(
+            querier,
+            KeeperQueryMsg::WhitelistedKeepers {
+                job_id,
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    /// Queries the vault for the readiness and schedule of a keeper job
+    pub fn query_keeper_job_status(
+        &self,
+        querier: &QuerierWrapper,
+        job_id: u64,
+    ) -> StdResult<KeeperJobStatus> {
+        self.query_extension(querier, KeeperQueryMsg::KeeperJobStatus { job_id })
+    }
+
+    /// Queries the vault for the rewards accrued by a keeper
+    pub fn query_keeper_rewards(
+        &self,
+        querier: &QuerierWrapper,
+        keeper: impl Into<String>,
+    ) -> StdResult<Vec<Coin>> {
+        self.query_extension(
+            querier,
+            KeeperQueryMsg::KeeperRewards {
+                keeper: keeper.into(),
+            },
+        )
+    }
+}
+
+/// Returns true if `err` is the error returned by a vault that does not know
+/// or does not support the queried variant, as opposed to e.g. an error while
+/// executing the query or deserializing its response.
+///
+/// Vaults reject unknown variants with the `unknown variant` message of serde,
+/// wrapped by the querier, so this relies on the error message. On chains that
+/// redact the errors of contract queries, the error is not recognized and is
+/// returned to the caller instead of falling back.
+fn is_unsupported_query(err: &StdError) -> bool {
+    let msg = err.to_string();
+    msg.contains("unknown variant") || msg.contains("not supported")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{from_json, Binary, Empty, SystemResult, WasmQuery};
+
+    #[test]
+    fn unknown_and_unsupported_variants_are_unsupported_queries() {
+        let unknown = StdError::generic_err(
+            "Querier contract error: Error parsing into type vault::msg::QueryMsg: unknown \
+             variant `vault_state`, expected one of `info`, `total_assets`",
+        );
+        let unsupported = StdError::generic_err(
+            "Querier contract error: Generic error: VaultState is not supported",
+        );
+        assert!(is_unsupported_query(&unknown));
+        assert!(is_unsupported_query(&unsupported));
+    }
+
+    #[test]
+    fn other_errors_are_not_unsupported_queries() {
+        let contract_error = StdError::generic_err("Querier contract error: vault is broken");
+        let parse_error = StdError::parse_err("VaultStateResponse", "missing field `paused`");
+        let system_error = StdError::generic_err("Querier system error: No such contract");
+        assert!(!is_unsupported_query(&contract_error));
+        assert!(!is_unsupported_query(&parse_error));
+        assert!(!is_unsupported_query(&system_error));
+    }
+
+    /// The `QueryMsg` of a vault using version 0.4.1 of the vault standard.
+    #[cw_serde]
+    enum V0_4_1QueryMsg {
+        VaultStandardInfo {},
+        Info {},
+        PreviewDeposit { amount: Uint128 },
+        PreviewRedeem { amount: Uint128 },
+        TotalAssets {},
+        TotalVaultTokenSupply {},
+        VaultTokenExchangeRate { quote_denom: String },
+        ConvertToShares { amount: Uint128 },
+        ConvertToAssets { amount: Uint128 },
+        VaultExtension(Empty),
+    }
+
+    /// Returns a querier in which every contract handles queries like the
+    /// query entry point of a 0.4.1 vault, which returns the error of parsing
+    /// the message for unknown variants.
+    fn v0_4_1_vault_querier() -> MockQuerier {
+        let mut querier = MockQuerier::new(&[]);
+        querier.update_wasm(|query| {
+            let WasmQuery::Smart { msg, .. } = query else {
+                panic!("unexpected query {query:?}");
+            };
+            let res = from_json::<V0_4_1QueryMsg>(msg).and_then(|msg| match msg {
+                V0_4_1QueryMsg::VaultStandardInfo {} => {
+                    Ok(br#"{"version":"0.4.1","extensions":["lockup"]}"#.into())
+                }
+                V0_4_1QueryMsg::Info {} => to_json_binary(&VaultInfoResponse {
+                    base_token: "base".to_string(),
+                    vault_token: "vault".to_string(),
+                }),
+                V0_4_1QueryMsg::TotalAssets {} => to_json_binary(&Uint128::new(200)),
+                V0_4_1QueryMsg::TotalVaultTokenSupply {} => to_json_binary(&Uint128::new(100)),
+                V0_4_1QueryMsg::VaultTokenExchangeRate { .. } => {
+                    to_json_binary(&Decimal::percent(200))
+                }
+                _ => Err(StdError::generic_err("not implemented")),
+            });
+            SystemResult::Ok(res.map_err(|err| err.to_string()).into())
+        });
+        querier
+    }
+
+    #[test]
+    fn query_state_falls_back_for_v0_4_1_vaults() {
+        let querier = v0_4_1_vault_querier();
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+        let vault = VaultContract::<ExtensionExecuteMsg, ExtensionQueryMsg>::new(
+            &querier,
+            &Addr::unchecked("vault"),
+        )
+        .unwrap();
+
+        let err = querier
+            .query_wasm_smart::<VaultStateResponse>(
+                &vault.addr,
+                &VaultStandardQueryMsg::<ExtensionQueryMsg>::VaultState {},
+            )
+            .unwrap_err();
+        assert!(err.to_string().starts_with(
+            "Generic error: Querier contract error: Error parsing into type \
+             cw_vault_standard::helper::tests::V0_4_1QueryMsg: unknown variant `vault_state`"
+        ));
+        assert!(is_unsupported_query(&err));
+
+        assert_eq!(
+            vault.query_state(&querier).unwrap(),
+            VaultStateResponse {
+                base_token: "base".to_string(),
+                vault_token: "vault".to_string(),
+                total_assets: Uint128::new(200),
+                total_vault_token_supply: Uint128::new(100),
+                vault_token_exchange_rate: Decimal::percent(200),
+                paused: false,
+                version: "0.4.1".to_string(),
+            }
+        );
+    }
+
+    #[cw_serde]
+    struct PingMsg {}
+
+    #[cw_serde]
+    enum TestExtensionExecuteMsg {
+        Ping(PingMsg),
+    }
+
+    impl From<PingMsg> for TestExtensionExecuteMsg {
+        fn from(msg: PingMsg) -> Self {
+            Self::Ping(msg)
+        }
+    }
+
+    #[test]
+    fn execute_extension_wraps_message_into_extension_enum() {
+        let vault = VaultContract::<TestExtensionExecuteMsg, Empty> {
+            addr: Addr::unchecked("vault"),
+            base_token: "base".to_string(),
+            vault_token: "vault".to_string(),
+            execute_msg_extension: PhantomData,
+            query_msg_extension: PhantomData,
+        };
+        let msg = vault.execute_extension(PingMsg {}, vec![]).unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "vault".to_string(),
+                msg: Binary::from(br#"{"vault_extension":{"ping":{}}}"#),
+                funds: vec![],
+            })
+        );
+    }
 }
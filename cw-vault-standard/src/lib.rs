@@ -26,6 +26,13 @@
 //! variants, please see the section on [how to use
 //! extensions](#how-to-use-extensions).
 //!
+//! Alternatively, the [framework] module can take care of the entrypoints for
+//! you. By implementing the [`VaultImpl`](framework::VaultImpl) trait, and the
+//! traits of any extensions your vault uses, the generic
+//! [`execute`](framework::execute) and [`query`](framework::query) functions
+//! handle message routing, funds validation, recipient defaulting, event
+//! emission and the `VaultStandardInfo` query.
+//!
 //! ## Description and specification of ExecuteMsg and QueryMsg variants
 //! Please refer to the documentation page for each of the enums
 //! [VaultStandardExecuteMsg] and [VaultStandardQueryMsg] for a complete
@@ -61,12 +68,52 @@
 //! Now you can use the `ExecuteMsg` enum in your contract entrypoints instead
 //! of the default [VaultStandardExecuteMsg] enum.
 //!
+//! To let other contracts build messages for your vault with the built-in
+//! extension messages, implement `From` for each of them on your extension
+//! enum. They then implement [`IntoExtension`], so that
+//! [`VaultStandardExecuteMsg::extension`] and the extension helpers of
+//! [`VaultContract`] wrap them into your enum:
+//!
+//! ```ignore
+//! impl From<LockupExecuteMsg> for ExtensionExecuteMsg {
+//!     fn from(msg: LockupExecuteMsg) -> Self {
+//!         ExtensionExecuteMsg::Lockup(msg)
+//!     }
+//! }
+//!
+//! let msg = ExecuteMsg::extension(LockupExecuteMsg::WithdrawUnlocked {
+//!     recipient: None,
+//!     lockup_id: 1,
+//! })
+//! .into_cosmos_msg(vault_addr, vec![])?;
+//! ```
+//!
+//! With the `derive` feature enabled, the [`vault_extensions`] attribute macro
+//! can generate the extension enums and `From` implementations for you, as
+//! well as the list of extensions returned in the `VaultStandardInfo` query.
+//! Built-in extensions are given by name, while custom extensions are given as
+//! `name = Prefix` for the `PrefixExecuteMsg` and `PrefixQueryMsg` enums:
+//!
+//! ```ignore
+//! #[vault_extensions(lockup, force_unlock, my_extension = MyExtension)]
+//! pub struct Extensions;
+//!
+//! pub type ExecuteMsg = VaultStandardExecuteMsg<ExtensionExecuteMsg>;
+//! pub type QueryMsg = VaultStandardQueryMsg<ExtensionQueryMsg>;
+//!
+//! let info = Extensions::vault_standard_info();
+//! ```
+//!
 //! ## Included Extensions
 //!
 //! The following extensions are included in this repo:
 //! * [Lockup](crate::extensions::lockup)
+//! * [LockupNft](crate::extensions::lockup_nft)
 //! * [ForceUnlock](crate::extensions::force_unlock)
 //! * [Keeper](crate::extensions::keeper)
+//! * [Strategies](crate::extensions::strategies)
+//! * [Harvest](crate::extensions::harvest)
+//! * [Analytics](crate::extensions::analytics)
 //! * [Cw4626](crate::extensions::cw4626)
 //!
 //! Each of these extensions are available in this repo via cargo features. To
@@ -86,6 +133,20 @@
 //! specified period of time before they can withdraw their base tokens via the
 //! `WithdrawUnlocked` variant.
 //!
+//! ### LockupNft
+//! The lockup NFT extension can be used together with the `Lockup` extension
+//! to make unlocking positions transferable. Each unlocking position is minted
+//! as a cw721 token, and the current owner of the token is the owner of the
+//! position: only they can withdraw it, by sending the token to the vault with
+//! the cw721 `SendNft` message, or by approving the vault for the token and
+//! calling `WithdrawUnlocked`. The cw721 contract is authoritative for
+//! ownership: the lockup queries resolve owners through it, and positions are
+//! transferred by transferring their token, so `TransferUnlockingPosition` is
+//! not supported. Since `SendNft` calls the vault with a top level
+//! `ReceiveNft` message, such vaults use `LockupNftExecuteMsg` instead of
+//! `VaultStandardExecuteMsg`. This allows pending unlocks to be traded on
+//! secondary markets or used as collateral.
+//!
 //! ### ForceUnlock
 //! The force unlock extension can be used to create a vault that also
 //! implements the `Lockup` extension, but where some whitelisted addresses are
@@ -97,7 +158,46 @@
 //! ### Keeper
 //! The keeper extension can be used to add functionality for either whitelisted
 //! addresses or anyone to act as a "keeper" for the vault and call functions to
-//! perform jobs that need to be done to keep the vault running.
+//! perform jobs that need to be done to keep the vault running. Each
+//! `KeeperJob` describes itself with an optional name, description, interval
+//! and parameters, and the `KeeperJobStatus` query returns when the job was
+//! last executed and when it can be executed next, so that off-chain keepers
+//! can schedule jobs without knowing the details of each vault.
+//! Jobs can also have a `KeeperBounty`, either a fixed amount of coins or a
+//! share of the harvested yield, which is accrued to permissionless keepers on
+//! successful execution and can be claimed with `ClaimKeeperRewards`. A minimum
+//! interval and a per-keeper cooldown prevent keepers from farming bounties.
+//! The vault admin can add and remove jobs with `AddKeeperJob` and
+//! `RemoveKeeperJob`, and manage the whitelists of jobs, as well as a global
+//! whitelist of keepers that can execute all jobs, in bulk with
+//! `UpdateKeeperWhitelist`.
+//!
+//! ### Strategies
+//! The strategies extension can be used to create vaults that deploy their base
+//! tokens into one or more strategies, similar to the allocator vaults of
+//! Yearn v3. Each strategy is an adapter contract implementing the
+//! `StrategyAdapterExecuteMsg` and `StrategyAdapterQueryMsg` interface and has
+//! a debt limit set by the vault admin. Keepers call `Allocate` and
+//! `Deallocate` to move base tokens between the vault and its strategies, and
+//! anyone can audit the current allocations via the `Strategies` query.
+//!
+//! ### Harvest
+//! The harvest extension can be used by auto-compounding vaults to expose what
+//! was earned by each harvest. Every call to `Harvest` persists a
+//! `HarvestReport` containing the gain, loss and fees taken, which can be read
+//! back via the `LastHarvest` and paginated `HarvestHistory` queries. The
+//! `PendingRewards` query lists rewards that have accrued but not yet been
+//! harvested, so yield dashboards can work the same way across vaults.
+//!
+//! ### Analytics
+//! The analytics extension can be used to create vaults that keep an on-chain
+//! history of their exchange rate, so that returns can be computed without an
+//! archive node. The vault snapshots its exchange rate and total assets at a
+//! configurable interval into a ring buffer, which can be read via the
+//! `ExchangeRateAt` and `ExchangeRateHistory` queries. The `RealizedApr` query
+//! returns the APR of the vault over a given window, and the
+//! `apr_from_snapshots` and `apy_from_snapshots` helper functions can be used to
+//! compute returns between any two snapshots.
 //!
 //! ### Cw4626
 //! The Cw4626 extension is the only extension provided with in this repo that
@@ -124,8 +224,46 @@ pub mod msg;
 /// Module containing a helper struct for interacting with a vault contract.
 pub mod helper;
 
+/// Module containing functions for validating funds and recipients of the
+/// vault standard messages, for use in vault implementations.
+pub mod validation;
+
+/// Module containing a trait-based framework for implementing vault contracts,
+/// with generic `execute` and `query` functions that dispatch the standard
+/// messages to implementations of the [`VaultImpl`](framework::VaultImpl)
+/// trait.
+pub mod framework;
+
+/// Module containing reusable storage building blocks for vault
+/// implementations, built on `cw-storage-plus`.
+#[cfg(feature = "storage")]
+#[cfg_attr(docsrs, doc(cfg(feature = "storage")))]
+pub mod storage;
+
+/// Module containing helpers to create, mint and burn vault tokens on chains
+/// with a tokenfactory module, or as CW20 tokens.
+#[cfg(feature = "vault-token")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vault-token")))]
+pub mod vault_token;
+
+/// Module containing the version 0.5 ExecuteMsg enums, in which the deprecated
+/// `amount` fields are removed, as well as helpers for upgrading vaults to
+/// them.
+pub mod v0_5;
+
+/// Module containing helpers for checking the compatibility between the
+/// version of the vault standard used by this crate and by a vault.
+pub mod compatibility;
+
 pub use helper::*;
 pub use msg::*;
 
+/// Attribute macro generating the `ExtensionExecuteMsg` and
+/// `ExtensionQueryMsg` enums of a vault from the list of extensions it uses.
+/// See the [how to use extensions](#how-to-use-extensions) section.
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use cw_vault_standard_derive::vault_extensions;
+
 /// The version of the vault standard.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,13 +1,22 @@
+#[cfg(feature = "analytics")]
+use crate::extensions::analytics::{AnalyticsExecuteMsg, AnalyticsQueryMsg};
 #[cfg(feature = "force-unlock")]
 use crate::extensions::force_unlock::ForceUnlockExecuteMsg;
+#[cfg(feature = "harvest")]
+use crate::extensions::harvest::{HarvestExecuteMsg, HarvestQueryMsg};
 #[cfg(feature = "keeper")]
 use crate::extensions::keeper::{KeeperExecuteMsg, KeeperQueryMsg};
 #[cfg(feature = "lockup")]
 use crate::extensions::lockup::{LockupExecuteMsg, LockupQueryMsg};
+#[cfg(feature = "lockup-nft")]
+use crate::extensions::lockup_nft::LockupNftQueryMsg;
+#[cfg(feature = "strategies")]
+use crate::extensions::strategies::{StrategiesExecuteMsg, StrategiesQueryMsg};
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, Empty, StdResult, Uint128, WasmMsg};
+use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, Decimal, Empty, StdResult, Uint128, WasmMsg};
 use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 /// The default ExecuteMsg variants that all vaults must implement.
 /// This enum can be extended with additional variants by defining an extension
@@ -56,7 +65,16 @@ pub enum VaultStandardExecuteMsg<T = ExtensionExecuteMsg> {
     VaultExtension(T),
 }
 
-impl VaultStandardExecuteMsg {
+impl<T> VaultStandardExecuteMsg<T>
+where
+    T: Serialize,
+{
+    /// Create a `VaultExtension` message from an extension message, wrapping
+    /// it into the extension enum `T`.
+    pub fn extension(msg: impl IntoExtension<T>) -> Self {
+        VaultStandardExecuteMsg::VaultExtension(msg.into_extension())
+    }
+
     /// Convert a [`VaultStandardExecuteMsg`] into a [`CosmosMsg`].
     pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
         Ok(WasmMsg::Execute {
@@ -68,6 +86,29 @@ impl VaultStandardExecuteMsg {
     }
 }
 
+/// Conversion of an extension message, such as
+/// [`LockupExecuteMsg`](crate::extensions::lockup::LockupExecuteMsg), into the
+/// extension enum `T` of a vault.
+///
+/// Implemented for every message that `T` implements `From` for, so to use
+/// the built-in extension messages with your own extension enum, implement
+/// `From<LockupExecuteMsg>` etc. for it. The messages can then be sent with
+/// [`VaultStandardExecuteMsg::<T>::extension`](VaultStandardExecuteMsg::extension)
+/// or the extension helpers of [`VaultContract`](crate::VaultContract).
+pub trait IntoExtension<T> {
+    /// Wrap this message into the extension enum `T`.
+    fn into_extension(self) -> T;
+}
+
+impl<M, T> IntoExtension<T> for M
+where
+    T: From<M>,
+{
+    fn into_extension(self) -> T {
+        T::from(self)
+    }
+}
+
 /// Contains ExecuteMsgs of all enabled extensions. To enable extensions defined
 /// outside of this crate, you can define your own `ExtensionExecuteMsg` type
 /// in your contract crate and pass it in as the generic parameter to ExecuteMsg
@@ -79,6 +120,12 @@ pub enum ExtensionExecuteMsg {
     Lockup(LockupExecuteMsg),
     #[cfg(feature = "force-unlock")]
     ForceUnlock(ForceUnlockExecuteMsg),
+    #[cfg(feature = "strategies")]
+    Strategies(StrategiesExecuteMsg),
+    #[cfg(feature = "harvest")]
+    Harvest(HarvestExecuteMsg),
+    #[cfg(feature = "analytics")]
+    Analytics(AnalyticsExecuteMsg),
 }
 
 /// The default QueryMsg variants that all vaults must implement.
@@ -192,6 +239,28 @@ where
         amount: Uint128,
     },
 
+    /// Returns `UserPositionResponse` with a summary of the position of
+    /// `address` in the vault, including its vault token balance, the
+    /// equivalent amount of base tokens and any amounts that are unlocking.
+    ///
+    /// This allows clients to display the position of a user with a single
+    /// query instead of combining a bank balance query, `ConvertToAssets` and
+    /// any extension queries.
+    #[returns(UserPositionResponse)]
+    UserPosition {
+        /// The address of the user to query the position for.
+        address: String,
+    },
+
+    /// Returns `VaultStateResponse` with the vault info, total assets, total
+    /// vault token supply, exchange rate, paused status and vault standard
+    /// version in a single response.
+    ///
+    /// Useful for contracts that need to read the state of many vaults in one
+    /// execution, as it replaces several separate queries.
+    #[returns(VaultStateResponse)]
+    VaultState {},
+
     /// Handle queries of any enabled extensions.
     #[returns(Empty)]
     VaultExtension(T),
@@ -206,22 +275,160 @@ pub enum ExtensionQueryMsg {
     Keeper(KeeperQueryMsg),
     #[cfg(feature = "lockup")]
     Lockup(LockupQueryMsg),
+    #[cfg(feature = "lockup-nft")]
+    LockupNft(LockupNftQueryMsg),
+    #[cfg(feature = "strategies")]
+    Strategies(StrategiesQueryMsg),
+    #[cfg(feature = "harvest")]
+    Harvest(HarvestQueryMsg),
+    #[cfg(feature = "analytics")]
+    Analytics(AnalyticsQueryMsg),
+}
+
+/// Implements `From<$msg>` for the extension enum `$enum` by wrapping the
+/// message in the `$variant` variant, if `$feature` is enabled.
+macro_rules! impl_from_extension_msg {
+    ($feature:literal, $enum:ident :: $variant:ident ($msg:ty)) => {
+        #[cfg(feature = $feature)]
+        impl From<$msg> for $enum {
+            fn from(msg: $msg) -> Self {
+                $enum::$variant(msg)
+            }
+        }
+    };
 }
 
+impl_from_extension_msg!("keeper", ExtensionExecuteMsg::Keeper(KeeperExecuteMsg));
+impl_from_extension_msg!("lockup", ExtensionExecuteMsg::Lockup(LockupExecuteMsg));
+impl_from_extension_msg!(
+    "force-unlock",
+    ExtensionExecuteMsg::ForceUnlock(ForceUnlockExecuteMsg)
+);
+impl_from_extension_msg!(
+    "strategies",
+    ExtensionExecuteMsg::Strategies(StrategiesExecuteMsg)
+);
+impl_from_extension_msg!("harvest", ExtensionExecuteMsg::Harvest(HarvestExecuteMsg));
+impl_from_extension_msg!(
+    "analytics",
+    ExtensionExecuteMsg::Analytics(AnalyticsExecuteMsg)
+);
+impl_from_extension_msg!("keeper", ExtensionQueryMsg::Keeper(KeeperQueryMsg));
+impl_from_extension_msg!("lockup", ExtensionQueryMsg::Lockup(LockupQueryMsg));
+impl_from_extension_msg!(
+    "lockup-nft",
+    ExtensionQueryMsg::LockupNft(LockupNftQueryMsg)
+);
+impl_from_extension_msg!(
+    "strategies",
+    ExtensionQueryMsg::Strategies(StrategiesQueryMsg)
+);
+impl_from_extension_msg!("harvest", ExtensionQueryMsg::Harvest(HarvestQueryMsg));
+impl_from_extension_msg!("analytics", ExtensionQueryMsg::Analytics(AnalyticsQueryMsg));
+
 /// Struct returned from QueryMsg::VaultStandardInfo with information about the
 /// used version of the vault standard and any extensions used.
 ///
 /// This struct should be stored as an Item under the `vault_standard_info` key,
 /// so that other contracts can do a RawQuery and read it directly from storage
 /// instead of needing to do a costly SmartQuery.
+///
+/// For compatibility with older vaults, extensions may also be deserialized
+/// from plain strings, e.g. `["lockup", "keeper"]`. Such extensions are assumed
+/// to have the same version as the vault standard.
+///
+/// Extensions are always serialized as [`ExtensionInfo`] objects, which clients
+/// using version 0.4.1 or older of the vault standard can not deserialize.
 #[cw_serde]
+#[serde(from = "VaultStandardInfoResponseRepr")]
 pub struct VaultStandardInfoResponse {
     /// The version of the vault standard used by the vault as a semver
     /// compliant string. E.g. "1.0.0" or "1.2.3-alpha.1"
     pub version: String,
-    /// A list of vault standard extensions used by the vault.
-    /// E.g. ["lockup", "keeper"]
-    pub extensions: Vec<String>,
+    /// A list of vault standard extensions used by the vault. Older vaults
+    /// return the names of the extensions as plain strings instead.
+    #[schemars(with = "Vec<ExtensionInfoRepr>")]
+    pub extensions: Vec<ExtensionInfo>,
+}
+
+impl VaultStandardInfoResponse {
+    /// Returns the info of the extension with the given name, if the vault
+    /// uses it.
+    pub fn extension(&self, name: &str) -> Option<&ExtensionInfo> {
+        self.extensions.iter().find(|ext| ext.name == name)
+    }
+
+    /// Returns true if the vault uses the extension with the given name at
+    /// version `min_version` or later. Returns false if either version is not
+    /// a valid semver string.
+    pub fn supports(&self, extension: &str, min_version: &str) -> bool {
+        let Ok(min_version) = semver::Version::parse(min_version) else {
+            return false;
+        };
+        self.extension(extension)
+            .and_then(|ext| semver::Version::parse(&ext.version).ok())
+            .is_some_and(|version| version >= min_version)
+    }
+}
+
+/// Describes a vault standard extension used by a vault.
+#[cw_serde]
+pub struct ExtensionInfo {
+    /// The name of the extension. Built-in extensions should use the
+    /// canonical names defined in [`crate::extensions`], e.g.
+    /// [`LOCKUP_EXTENSION_NAME`](crate::extensions::LOCKUP_EXTENSION_NAME).
+    pub name: String,
+    /// The version of the extension as a semver compliant string. For built-in
+    /// extensions this is the version of the vault standard that defines it.
+    pub version: String,
+    /// A list of optional features of the extension that the vault supports.
+    pub features: Vec<String>,
+}
+
+impl ExtensionInfo {
+    /// Create a new ExtensionInfo without any optional features.
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            features: vec![],
+        }
+    }
+}
+
+/// Wire format of [`VaultStandardInfoResponse`] that accepts extensions both
+/// as plain strings and as structured [`ExtensionInfo`] descriptors.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VaultStandardInfoResponseRepr {
+    version: String,
+    extensions: Vec<ExtensionInfoRepr>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(rename = "ExtensionInfoOrName")]
+enum ExtensionInfoRepr {
+    Name(String),
+    Info(ExtensionInfo),
+}
+
+impl From<VaultStandardInfoResponseRepr> for VaultStandardInfoResponse {
+    fn from(repr: VaultStandardInfoResponseRepr) -> Self {
+        let extensions = repr
+            .extensions
+            .into_iter()
+            .map(|ext| match ext {
+                ExtensionInfoRepr::Name(name) => ExtensionInfo::new(name, &repr.version),
+                ExtensionInfoRepr::Info(info) => info,
+            })
+            .collect();
+
+        Self {
+            version: repr.version,
+            extensions,
+        }
+    }
 }
 
 /// Returned by QueryMsg::Info and contains information about this vault
@@ -235,3 +442,101 @@ pub struct VaultInfoResponse {
     /// if it is a cw20 token.
     pub vault_token: String,
 }
+
+/// Returned by QueryMsg::UserPosition and contains a summary of the position of
+/// a user in the vault.
+#[cw_serde]
+pub struct UserPositionResponse {
+    /// The amount of vault tokens held by the user.
+    pub vault_token_balance: Uint128,
+    /// The amount of base tokens that `vault_token_balance` is worth, as
+    /// returned by QueryMsg::ConvertToAssets.
+    pub base_token_amount: Uint128,
+    /// The sum of the base token amounts of all unlocking positions of the user
+    /// that have not finished unlocking yet. Always zero for vaults that do not
+    /// implement the Lockup extension.
+    pub unlocking_base_token_amount: Uint128,
+    /// The sum of the base token amounts of all unlocking positions of the user
+    /// that have finished unlocking and can be withdrawn. Always zero for vaults
+    /// that do not implement the Lockup extension.
+    pub claimable_base_token_amount: Uint128,
+    /// The amount of base tokens the user has deposited into the vault, net of
+    /// withdrawals, for the purpose of calculating profit and loss. `None` if
+    /// the vault does not track cost basis.
+    pub cost_basis: Option<Uint128>,
+}
+
+/// Returned by QueryMsg::VaultState and contains the aggregated state of the
+/// vault.
+#[cw_serde]
+pub struct VaultStateResponse {
+    /// The base token of the vault, as returned by QueryMsg::Info.
+    pub base_token: String,
+    /// The vault token of the vault, as returned by QueryMsg::Info.
+    pub vault_token: String,
+    /// The amount of assets managed by the vault denominated in base tokens,
+    /// as returned by QueryMsg::TotalAssets.
+    pub total_assets: Uint128,
+    /// The total amount of vault tokens in circulation, as returned by
+    /// QueryMsg::TotalVaultTokenSupply.
+    pub total_vault_token_supply: Uint128,
+    /// The amount of base tokens that can be exchanged for 1 unit of vault
+    /// tokens, as returned by QueryMsg::VaultTokenExchangeRate with the base
+    /// token as quote denom.
+    pub vault_token_exchange_rate: Decimal,
+    /// Whether deposits and redemptions are currently paused. Vaults that do
+    /// not support the `VaultState` query can not report this, so
+    /// [`VaultContract::query_state`](crate::VaultContract::query_state)
+    /// returns `false` for them.
+    pub paused: bool,
+    /// The version of the vault standard used by the vault, as returned by
+    /// QueryMsg::VaultStandardInfo.
+    pub version: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::{from_json, to_json_string};
+
+    #[test]
+    fn legacy_extension_names_are_deserialized() {
+        let info: VaultStandardInfoResponse =
+            from_json(br#"{"version":"0.4.1","extensions":["lockup","keeper"]}"#).unwrap();
+        assert_eq!(
+            info,
+            VaultStandardInfoResponse {
+                version: "0.4.1".to_string(),
+                extensions: vec![
+                    ExtensionInfo::new("lockup", "0.4.1"),
+                    ExtensionInfo::new("keeper", "0.4.1"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn extension_infos_are_serialized_as_objects() {
+        let info = VaultStandardInfoResponse {
+            version: "0.4.1".to_string(),
+            extensions: vec![ExtensionInfo::new("lockup", "0.4.1")],
+        };
+        let json = to_json_string(&info).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":"0.4.1","extensions":[{"name":"lockup","version":"0.4.1","features":[]}]}"#
+        );
+        assert_eq!(from_json::<VaultStandardInfoResponse>(&json).unwrap(), info);
+    }
+
+    #[test]
+    fn schema_allows_legacy_extension_names() {
+        let schema = schemars::schema_for!(VaultStandardInfoResponse);
+        let json = to_json_string(&schema).unwrap();
+        assert!(json.contains(r##""items":{"$ref":"#/definitions/ExtensionInfoOrName"}"##));
+        assert!(json.contains(
+            r##""ExtensionInfoOrName":{"anyOf":[{"type":"string"},{"$ref":"#/definitions/ExtensionInfo"}]}"##
+        ));
+    }
+}
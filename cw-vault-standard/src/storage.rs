@@ -0,0 +1,1047 @@
+use cosmwasm_std::{Addr, Empty, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+
+#[cfg(feature = "analytics")]
+use crate::extensions::analytics::{apr_from_snapshots, ExchangeRateSnapshot, SnapshotConfig};
+#[cfg(feature = "keeper")]
+use crate::extensions::keeper::{KeeperJob, KeeperJobConfig, KeeperJobExecution};
+#[cfg(feature = "lockup")]
+use crate::extensions::lockup::UnlockingPosition;
+#[cfg(any(feature = "lockup", feature = "analytics"))]
+use cosmwasm_std::Timestamp;
+#[cfg(feature = "keeper")]
+use cosmwasm_std::{BlockInfo, Coin};
+#[cfg(feature = "analytics")]
+use cosmwasm_std::{Decimal, StdError};
+#[cfg(feature = "lockup")]
+use cw_storage_plus::{Index, IndexList, IndexedMap, MultiIndex};
+#[cfg(feature = "lockup")]
+use cw_utils::Expiration;
+
+use crate::VaultStandardInfoResponse;
+
+/// The default number of results returned by paginated queries.
+pub const DEFAULT_LIMIT: u32 = 10;
+/// The maximum number of results returned by paginated queries.
+pub const MAX_LIMIT: u32 = 30;
+
+/// Returns the number of results to return for a paginated query, given the
+/// optional `limit` passed in the query.
+pub fn calc_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
+/// The `VaultStandardInfoResponse` of the vault, stored under the
+/// `vault_standard_info` key so that other contracts can read it with a raw
+/// query.
+pub const VAULT_STANDARD_INFO: Item<VaultStandardInfoResponse> = Item::new("vault_standard_info");
+
+/// Tracks the total supply of vault tokens, for vaults where the supply can
+/// not be queried from the bank module.
+pub struct VaultTokenSupply<'a>(Item<'a, Uint128>);
+
+impl<'a> VaultTokenSupply<'a> {
+    /// Create a new VaultTokenSupply stored under `storage_key`.
+    pub const fn new(storage_key: &'a str) -> Self {
+        Self(Item::new(storage_key))
+    }
+
+    /// Returns the total supply of vault tokens. Zero if nothing was minted yet.
+    pub fn load(&self, storage: &dyn Storage) -> StdResult<Uint128> {
+        Ok(self.0.may_load(storage)?.unwrap_or_default())
+    }
+
+    /// Increases the total supply by `amount` and returns the new total supply.
+    pub fn increase(&self, storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+        let supply = self.load(storage)?.checked_add(amount)?;
+        self.0.save(storage, &supply)?;
+        Ok(supply)
+    }
+
+    /// Decreases the total supply by `amount` and returns the new total supply.
+    pub fn decrease(&self, storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+        let supply = self.load(storage)?.checked_sub(amount)?;
+        self.0.save(storage, &supply)?;
+        Ok(supply)
+    }
+}
+
+/// Indexes of [`UnlockingPositions`].
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub struct UnlockingPositionIndexes<'a> {
+    /// Index of the unlocking positions by owner.
+    pub owner: MultiIndex<'a, Addr, UnlockingPosition, u64>,
+    /// Index of the unlocking positions by the time at which they finish
+    /// unlocking, see [`release_time_key`].
+    pub release_at: MultiIndex<'a, u64, UnlockingPosition, u64>,
+}
+
+/// Returns the key of `position` in the `release_at` index of
+/// [`UnlockingPositions`]: the time at which it finishes unlocking in
+/// seconds, or `u64::MAX` if its `release_at` is a block height or never, so
+/// that it sorts after all positions with a release time.
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub fn release_time_key(position: &UnlockingPosition) -> u64 {
+    match position.release_at {
+        Expiration::AtTime(release_at) => release_at.seconds(),
+        Expiration::AtHeight(_) | Expiration::Never {} => u64::MAX,
+    }
+}
+
+#[cfg(feature = "lockup")]
+impl<'a> IndexList<UnlockingPosition> for UnlockingPositionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<UnlockingPosition>> + '_> {
+        let v: Vec<&dyn Index<UnlockingPosition>> = vec![&self.owner, &self.release_at];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Stores the unlocking positions of a vault that implements the Lockup
+/// extension, keyed by lockup ID and indexed by owner and release time.
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub struct UnlockingPositions<'a> {
+    positions: IndexedMap<'a, u64, UnlockingPosition, UnlockingPositionIndexes<'a>>,
+    next_id: Item<'a, u64>,
+}
+
+#[cfg(feature = "lockup")]
+impl<'a> UnlockingPositions<'a> {
+    /// Create a new UnlockingPositions, storing the positions under
+    /// `pk_namespace`, the owner index under `owner_namespace`, the release
+    /// time index under `release_at_namespace` and the next lockup ID under
+    /// `next_id_namespace`.
+    pub const fn new(
+        pk_namespace: &'a str,
+        owner_namespace: &'a str,
+        release_at_namespace: &'a str,
+        next_id_namespace: &'a str,
+    ) -> Self {
+        Self {
+            positions: IndexedMap::new(
+                pk_namespace,
+                UnlockingPositionIndexes {
+                    owner: MultiIndex::new(
+                        |_pk, position| position.owner.clone(),
+                        pk_namespace,
+                        owner_namespace,
+                    ),
+                    release_at: MultiIndex::new(
+                        |_pk, position| release_time_key(position),
+                        pk_namespace,
+                        release_at_namespace,
+                    ),
+                },
+            ),
+            next_id: Item::new(next_id_namespace),
+        }
+    }
+
+    /// Returns the ID to use for a new unlocking position and increments the
+    /// stored next ID. IDs start from 0.
+    pub fn next_id(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let id = self.next_id.may_load(storage)?.unwrap_or_default();
+        self.next_id.save(storage, &(id + 1))?;
+        Ok(id)
+    }
+
+    /// Saves `position` under its ID, replacing any existing position with the
+    /// same ID.
+    pub fn save(&self, storage: &mut dyn Storage, position: &UnlockingPosition) -> StdResult<()> {
+        self.positions.save(storage, position.id, position)
+    }
+
+    /// Returns the unlocking position with ID `lockup_id`.
+    pub fn load(&self, storage: &dyn Storage, lockup_id: u64) -> StdResult<UnlockingPosition> {
+        self.positions.load(storage, lockup_id)
+    }
+
+    /// Removes the unlocking position with ID `lockup_id`.
+    pub fn remove(&self, storage: &mut dyn Storage, lockup_id: u64) -> StdResult<()> {
+        self.positions.remove(storage, lockup_id)
+    }
+
+    /// Returns the unlocking positions of all owners, ordered by ID. Matches
+    /// the `AllUnlockingPositions` query of the Lockup extension.
+    pub fn all(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.positions
+            .range(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .map(|res| res.map(|(_, position)| position))
+            .collect()
+    }
+
+    /// Returns the unlocking positions of `owner`, ordered by ID. Matches the
+    /// `UnlockingPositions` query of the Lockup extension.
+    pub fn by_owner(
+        &self,
+        storage: &dyn Storage,
+        owner: &Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.positions
+            .idx
+            .owner
+            .prefix(owner.clone())
+            .range(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .map(|res| res.map(|(_, position)| position))
+            .collect()
+    }
+
+    /// Returns the unlocking positions of all owners that finish unlocking at
+    /// or before `time`, ordered by release time and then by ID. Matches the
+    /// `UnlockingPositionsMaturingBefore` query of the Lockup extension.
+    /// Positions with a `release_at` in block height are not returned.
+    pub fn maturing_before(
+        &self,
+        storage: &dyn Storage,
+        time: Timestamp,
+        start_after: Option<(Timestamp, u64)>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnlockingPosition>> {
+        self.positions
+            .idx
+            .release_at
+            .range(
+                storage,
+                start_after.map(|(release_at, id)| Bound::exclusive((release_at.seconds(), id))),
+                Some(Bound::inclusive((time.seconds(), u64::MAX))),
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .map(|res| res.map(|(_, position)| position))
+            .collect()
+    }
+}
+
+/// Stores the keeper jobs of a vault that implements the Keeper extension,
+/// keyed by job ID.
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub struct KeeperJobs<'a> {
+    jobs: Map<'a, u64, KeeperJob>,
+    next_id: Item<'a, u64>,
+}
+
+#[cfg(feature = "keeper")]
+impl<'a> KeeperJobs<'a> {
+    /// Create a new KeeperJobs, storing the jobs under `namespace` and the
+    /// next job ID under `next_id_namespace`.
+    pub const fn new(namespace: &'a str, next_id_namespace: &'a str) -> Self {
+        Self {
+            jobs: Map::new(namespace),
+            next_id: Item::new(next_id_namespace),
+        }
+    }
+
+    /// Returns the ID to use for a new keeper job and increments the stored
+    /// next ID. IDs start from 0 and are not reused after a job is removed.
+    pub fn next_id(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let id = self.next_id.may_load(storage)?.unwrap_or_default();
+        self.next_id.save(storage, &(id + 1))?;
+        Ok(id)
+    }
+
+    /// Saves a new keeper job with the next ID and the given configuration,
+    /// and returns it.
+    pub fn add(&self, storage: &mut dyn Storage, config: KeeperJobConfig) -> StdResult<KeeperJob> {
+        let job = config.into_job(self.next_id(storage)?);
+        self.save(storage, &job)?;
+        Ok(job)
+    }
+
+    /// Saves `job` under its ID, replacing any existing job with the same ID.
+    pub fn save(&self, storage: &mut dyn Storage, job: &KeeperJob) -> StdResult<()> {
+        self.jobs.save(storage, job.id, job)
+    }
+
+    /// Returns the keeper job with ID `job_id`.
+    pub fn load(&self, storage: &dyn Storage, job_id: u64) -> StdResult<KeeperJob> {
+        self.jobs.load(storage, job_id)
+    }
+
+    /// Records that the keeper job with ID `job_id` was executed at `block`
+    /// and returns the updated job.
+    pub fn record_execution(
+        &self,
+        storage: &mut dyn Storage,
+        job_id: u64,
+        block: &BlockInfo,
+    ) -> StdResult<KeeperJob> {
+        let mut job = self.load(storage, job_id)?;
+        job.record_execution(block);
+        self.save(storage, &job)?;
+        Ok(job)
+    }
+
+    /// Removes the keeper job with ID `job_id`.
+    pub fn remove(&self, storage: &mut dyn Storage, job_id: u64) {
+        self.jobs.remove(storage, job_id)
+    }
+
+    /// Returns the keeper jobs, ordered by ID.
+    pub fn range(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<KeeperJob>> {
+        self.jobs
+            .range(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .map(|res| res.map(|(_, job)| job))
+            .collect()
+    }
+}
+
+/// Stores the keeper rewards accrued by each keeper of a vault that implements
+/// the Keeper extension, and the execution for which each keeper was last paid
+/// a bounty for each job.
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub struct KeeperRewards<'a> {
+    rewards: Map<'a, &'a Addr, Vec<Coin>>,
+    last_paid: Map<'a, (u64, &'a Addr), KeeperJobExecution>,
+}
+
+#[cfg(feature = "keeper")]
+impl<'a> KeeperRewards<'a> {
+    /// Create a new KeeperRewards, storing the accrued rewards under
+    /// `namespace` and the last payments under `last_paid_namespace`.
+    pub const fn new(namespace: &'a str, last_paid_namespace: &'a str) -> Self {
+        Self {
+            rewards: Map::new(namespace),
+            last_paid: Map::new(last_paid_namespace),
+        }
+    }
+
+    /// Returns the rewards accrued by `keeper`.
+    pub fn load(&self, storage: &dyn Storage, keeper: &Addr) -> StdResult<Vec<Coin>> {
+        Ok(self.rewards.may_load(storage, keeper)?.unwrap_or_default())
+    }
+
+    /// Returns the execution for which `keeper` was last paid a bounty for the
+    /// job with ID `job_id`, if any.
+    pub fn last_paid(
+        &self,
+        storage: &dyn Storage,
+        job_id: u64,
+        keeper: &Addr,
+    ) -> StdResult<Option<KeeperJobExecution>> {
+        self.last_paid.may_load(storage, (job_id, keeper))
+    }
+
+    /// Adds `rewards` to the rewards accrued by `keeper` as the bounty for
+    /// executing the job with ID `job_id` at `block`, and returns the new
+    /// accrued rewards.
+    pub fn pay_bounty(
+        &self,
+        storage: &mut dyn Storage,
+        job_id: u64,
+        keeper: &Addr,
+        rewards: &[Coin],
+        block: &BlockInfo,
+    ) -> StdResult<Vec<Coin>> {
+        self.last_paid.save(
+            storage,
+            (job_id, keeper),
+            &KeeperJobExecution {
+                height: block.height,
+                time: block.time,
+            },
+        )?;
+        self.accrue(storage, keeper, rewards)
+    }
+
+    /// Adds `rewards` to the rewards accrued by `keeper` and returns the new
+    /// accrued rewards.
+    pub fn accrue(
+        &self,
+        storage: &mut dyn Storage,
+        keeper: &Addr,
+        rewards: &[Coin],
+    ) -> StdResult<Vec<Coin>> {
+        let mut accrued = self.load(storage, keeper)?;
+        for reward in rewards {
+            match accrued.iter_mut().find(|coin| coin.denom == reward.denom) {
+                Some(coin) => coin.amount = coin.amount.checked_add(reward.amount)?,
+                None => accrued.push(reward.clone()),
+            }
+        }
+        self.rewards.save(storage, keeper, &accrued)?;
+        Ok(accrued)
+    }
+
+    /// Removes and returns the rewards accrued by `keeper`, for use when they
+    /// are claimed.
+    pub fn claim(&self, storage: &mut dyn Storage, keeper: &Addr) -> StdResult<Vec<Coin>> {
+        let accrued = self.load(storage, keeper)?;
+        self.rewards.remove(storage, keeper);
+        Ok(accrued)
+    }
+}
+
+/// A set of addresses, such as a whitelist.
+pub struct AddressSet<'a>(Map<'a, &'a Addr, Empty>);
+
+impl<'a> AddressSet<'a> {
+    /// Create a new AddressSet stored under `namespace`.
+    pub const fn new(namespace: &'a str) -> Self {
+        Self(Map::new(namespace))
+    }
+
+    /// Returns true if `addr` is in the set.
+    pub fn contains(&self, storage: &dyn Storage, addr: &Addr) -> bool {
+        self.0.has(storage, addr)
+    }
+
+    /// Adds `addr` to the set.
+    pub fn insert(&self, storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+        self.0.save(storage, addr, &Empty {})
+    }
+
+    /// Removes `addr` from the set.
+    pub fn remove(&self, storage: &mut dyn Storage, addr: &Addr) {
+        self.0.remove(storage, addr)
+    }
+
+    /// Adds all of `add` to the set and then removes all of `remove` from it.
+    /// Matches the shape of `UpdateForceWithdrawWhitelist` and
+    /// `UpdateKeeperWhitelist`.
+    pub fn update(
+        &self,
+        storage: &mut dyn Storage,
+        add: &[Addr],
+        remove: &[Addr],
+    ) -> StdResult<()> {
+        for addr in add {
+            self.insert(storage, addr)?;
+        }
+        for addr in remove {
+            self.remove(storage, addr);
+        }
+        Ok(())
+    }
+
+    /// Returns the addresses in the set, in ascending order.
+    pub fn range(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        self.0
+            .keys(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .collect()
+    }
+}
+
+/// The set of addresses that are allowed to call `ForceRedeem` and
+/// `ForceWithdrawUnlocking` on a vault that implements the ForceUnlock
+/// extension.
+#[cfg(feature = "force-unlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "force-unlock")))]
+pub type ForceWithdrawWhitelist<'a> = AddressSet<'a>;
+
+/// The keeper whitelists of a vault that implements the Keeper extension: the
+/// whitelist of each job, keyed by job ID and address, and the global keeper
+/// whitelist containing the keepers that are allowed to execute all jobs. The
+/// functions take the ID of the job, or `None` for the global whitelist, like
+/// `UpdateKeeperWhitelist` and `WhitelistedKeepers`.
+#[cfg(feature = "keeper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keeper")))]
+pub struct KeeperWhitelists<'a> {
+    jobs: Map<'a, (u64, &'a Addr), Empty>,
+    global: AddressSet<'a>,
+}
+
+#[cfg(feature = "keeper")]
+impl<'a> KeeperWhitelists<'a> {
+    /// Create a new KeeperWhitelists, storing the whitelists of jobs under
+    /// `namespace` and the global keeper whitelist under `global_namespace`.
+    pub const fn new(namespace: &'a str, global_namespace: &'a str) -> Self {
+        Self {
+            jobs: Map::new(namespace),
+            global: AddressSet::new(global_namespace),
+        }
+    }
+
+    /// Returns true if `keeper` is on the whitelist of the job with ID
+    /// `job_id`, or on the global whitelist if `job_id` is `None`.
+    pub fn contains(&self, storage: &dyn Storage, job_id: Option<u64>, keeper: &Addr) -> bool {
+        match job_id {
+            Some(job_id) => self.jobs.has(storage, (job_id, keeper)),
+            None => self.global.contains(storage, keeper),
+        }
+    }
+
+    /// Returns true if `keeper` may execute the job with ID `job_id`, i.e. if
+    /// it is on the whitelist of the job or on the global whitelist.
+    pub fn is_whitelisted(&self, storage: &dyn Storage, job_id: u64, keeper: &Addr) -> bool {
+        self.contains(storage, None, keeper) || self.contains(storage, Some(job_id), keeper)
+    }
+
+    /// Adds `keeper` to the whitelist of the job with ID `job_id`, or to the
+    /// global whitelist if `job_id` is `None`.
+    pub fn insert(
+        &self,
+        storage: &mut dyn Storage,
+        job_id: Option<u64>,
+        keeper: &Addr,
+    ) -> StdResult<()> {
+        match job_id {
+            Some(job_id) => self.jobs.save(storage, (job_id, keeper), &Empty {}),
+            None => self.global.insert(storage, keeper),
+        }
+    }
+
+    /// Removes `keeper` from the whitelist of the job with ID `job_id`, or
+    /// from the global whitelist if `job_id` is `None`.
+    pub fn remove(&self, storage: &mut dyn Storage, job_id: Option<u64>, keeper: &Addr) {
+        match job_id {
+            Some(job_id) => self.jobs.remove(storage, (job_id, keeper)),
+            None => self.global.remove(storage, keeper),
+        }
+    }
+
+    /// Adds all of `add` to and then removes all of `remove` from the
+    /// whitelist of the job with ID `job_id`, or the global whitelist if
+    /// `job_id` is `None`. Matches the shape of `UpdateKeeperWhitelist`.
+    pub fn update(
+        &self,
+        storage: &mut dyn Storage,
+        job_id: Option<u64>,
+        add: &[Addr],
+        remove: &[Addr],
+    ) -> StdResult<()> {
+        for keeper in add {
+            self.insert(storage, job_id, keeper)?;
+        }
+        for keeper in remove {
+            self.remove(storage, job_id, keeper);
+        }
+        Ok(())
+    }
+
+    /// Returns the keepers on the whitelist of the job with ID `job_id`, or on
+    /// the global whitelist if `job_id` is `None`, in ascending order. Matches
+    /// the `WhitelistedKeepers` query of the Keeper extension.
+    pub fn range(
+        &self,
+        storage: &dyn Storage,
+        job_id: Option<u64>,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        let Some(job_id) = job_id else {
+            return self.global.range(storage, start_after, limit);
+        };
+        self.jobs
+            .prefix(job_id)
+            .keys(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .collect()
+    }
+
+    /// Removes the whitelist of the job with ID `job_id`, for use when the job
+    /// is removed.
+    pub fn remove_job(&self, storage: &mut dyn Storage, job_id: u64) -> StdResult<()> {
+        let keepers = self
+            .jobs
+            .prefix(job_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for keeper in keepers {
+            self.jobs.remove(storage, (job_id, &keeper));
+        }
+        Ok(())
+    }
+}
+
+/// Stores the exchange rate snapshots of a vault that implements the Analytics
+/// extension, together with its `SnapshotConfig`. Acts as a ring buffer: the
+/// snapshots are keyed by time, and once `capacity` snapshots are stored,
+/// taking a new one removes the oldest.
+#[cfg(feature = "analytics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analytics")))]
+pub struct ExchangeRateSnapshots<'a> {
+    snapshots: Map<'a, u64, ExchangeRateSnapshot>,
+    len: Item<'a, u32>,
+    config: Item<'a, SnapshotConfig>,
+}
+
+#[cfg(feature = "analytics")]
+impl<'a> ExchangeRateSnapshots<'a> {
+    /// Create a new ExchangeRateSnapshots, storing the snapshots under
+    /// `namespace`, the number of stored snapshots under `len_namespace` and
+    /// the snapshot configuration under `config_namespace`.
+    pub const fn new(
+        namespace: &'a str,
+        len_namespace: &'a str,
+        config_namespace: &'a str,
+    ) -> Self {
+        Self {
+            snapshots: Map::new(namespace),
+            len: Item::new(len_namespace),
+            config: Item::new(config_namespace),
+        }
+    }
+
+    /// Returns the snapshot configuration. Matches the `SnapshotConfig` query
+    /// of the Analytics extension.
+    pub fn config(&self, storage: &dyn Storage) -> StdResult<SnapshotConfig> {
+        self.config.load(storage)
+    }
+
+    /// Saves the snapshot configuration and removes the oldest snapshots that
+    /// no longer fit in the new capacity. Returns an error if the capacity is
+    /// zero. Matches `UpdateSnapshotConfig`.
+    pub fn update_config(
+        &self,
+        storage: &mut dyn Storage,
+        config: SnapshotConfig,
+    ) -> StdResult<()> {
+        if config.capacity == 0 {
+            return Err(StdError::generic_err(
+                "snapshot capacity must be at least one",
+            ));
+        }
+        self.config.save(storage, &config)?;
+        self.truncate(storage, config.capacity)
+    }
+
+    /// Returns the number of stored snapshots.
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        Ok(self.len.may_load(storage)?.unwrap_or_default())
+    }
+
+    /// Returns true if no snapshots are stored.
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Stores `snapshot`, removing the oldest snapshot if the buffer is full.
+    /// Returns an error if the configuration is not set, or if less than
+    /// `interval_seconds` have passed since the latest snapshot. Matches
+    /// `TakeSnapshot`.
+    pub fn take(
+        &self,
+        storage: &mut dyn Storage,
+        snapshot: &ExchangeRateSnapshot,
+    ) -> StdResult<()> {
+        let config = self.config(storage)?;
+        if let Some(latest) = self.latest(storage)? {
+            let next = latest.time.plus_seconds(config.interval_seconds);
+            if snapshot.time <= latest.time || snapshot.time < next {
+                return Err(StdError::generic_err(format!(
+                    "next snapshot can not be taken before {next}"
+                )));
+            }
+        }
+        self.snapshots
+            .save(storage, snapshot.time.nanos(), snapshot)?;
+        let len = self.len(storage)? + 1;
+        self.len.save(storage, &len)?;
+        self.truncate(storage, config.capacity)
+    }
+
+    /// Returns the latest snapshot, if any.
+    pub fn latest(&self, storage: &dyn Storage) -> StdResult<Option<ExchangeRateSnapshot>> {
+        self.snapshots
+            .range(storage, None, None, Order::Descending)
+            .next()
+            .map(|res| res.map(|(_, snapshot)| snapshot))
+            .transpose()
+    }
+
+    /// Returns the latest snapshot taken at or before `time`. Returns an error
+    /// if there is no such snapshot. Matches the `ExchangeRateAt` query of the
+    /// Analytics extension.
+    pub fn at(&self, storage: &dyn Storage, time: Timestamp) -> StdResult<ExchangeRateSnapshot> {
+        self.snapshots
+            .range(
+                storage,
+                None,
+                Some(Bound::inclusive(time.nanos())),
+                Order::Descending,
+            )
+            .next()
+            .transpose()?
+            .map(|(_, snapshot)| snapshot)
+            .ok_or_else(|| StdError::not_found(format!("exchange rate snapshot at {time}")))
+    }
+
+    /// Returns the stored snapshots taken after `start_after`, ordered by time.
+    /// Matches the `ExchangeRateHistory` query of the Analytics extension.
+    pub fn history(
+        &self,
+        storage: &dyn Storage,
+        start_after: Option<Timestamp>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<ExchangeRateSnapshot>> {
+        self.snapshots
+            .range(
+                storage,
+                start_after.map(|time| Bound::exclusive(time.nanos())),
+                None,
+                Order::Ascending,
+            )
+            .take(calc_limit(limit))
+            .map(|res| res.map(|(_, snapshot)| snapshot))
+            .collect()
+    }
+
+    /// Returns the APR of the vault over the last `window_seconds` seconds,
+    /// computed with [`apr_from_snapshots`] from the latest snapshot and the
+    /// snapshot taken at or before the start of the window. Matches the
+    /// `RealizedApr` query of the Analytics extension.
+    pub fn realized_apr(&self, storage: &dyn Storage, window_seconds: u64) -> StdResult<Decimal> {
+        let end = self
+            .latest(storage)?
+            .ok_or_else(|| StdError::not_found("exchange rate snapshot"))?;
+        let start = self.at(storage, end.time.minus_seconds(window_seconds))?;
+        apr_from_snapshots(&start, &end)
+    }
+
+    /// Removes the oldest snapshots until at most `capacity` are stored.
+    fn truncate(&self, storage: &mut dyn Storage, capacity: u32) -> StdResult<()> {
+        let len = self.len(storage)?;
+        if len <= capacity {
+            return Ok(());
+        }
+        let oldest = self
+            .snapshots
+            .keys(storage, None, None, Order::Ascending)
+            .take((len - capacity) as usize)
+            .collect::<StdResult<Vec<_>>>()?;
+        for key in oldest {
+            self.snapshots.remove(storage, key);
+        }
+        self.len.save(storage, &capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "lockup")]
+    use cosmwasm_std::testing::MockStorage;
+
+    #[cfg(feature = "lockup")]
+    const POSITIONS: UnlockingPositions = UnlockingPositions::new(
+        "positions",
+        "positions__owner",
+        "positions__release",
+        "next_id",
+    );
+
+    #[cfg(feature = "lockup")]
+    fn save_position(storage: &mut dyn Storage, release_at: Expiration) -> u64 {
+        let id = POSITIONS.next_id(storage).unwrap();
+        POSITIONS
+            .save(
+                storage,
+                &UnlockingPosition {
+                    id,
+                    owner: Addr::unchecked("owner"),
+                    release_at,
+                    base_token_amount: Uint128::new(100),
+                    tier: None,
+                    vault_token_amount: Uint128::new(100),
+                    created_at: Timestamp::default(),
+                },
+            )
+            .unwrap();
+        id
+    }
+
+    #[cfg(feature = "lockup")]
+    fn ids(positions: Vec<UnlockingPosition>) -> Vec<u64> {
+        positions.into_iter().map(|position| position.id).collect()
+    }
+
+    #[test]
+    fn limit_is_capped() {
+        assert_eq!(calc_limit(None), DEFAULT_LIMIT as usize);
+        assert_eq!(calc_limit(Some(5)), 5);
+        assert_eq!(calc_limit(Some(MAX_LIMIT + 1)), MAX_LIMIT as usize);
+    }
+
+    #[test]
+    #[cfg(feature = "lockup")]
+    fn maturing_before_ranges_over_release_time() {
+        let mut storage = MockStorage::new();
+        let at = |seconds| Expiration::AtTime(Timestamp::from_seconds(seconds));
+        save_position(&mut storage, at(300));
+        save_position(&mut storage, at(100));
+        save_position(&mut storage, Expiration::AtHeight(1));
+        save_position(&mut storage, at(200));
+        save_position(&mut storage, at(100));
+        save_position(&mut storage, Expiration::Never {});
+
+        let maturing = |time, start_after, limit| {
+            ids(POSITIONS
+                .maturing_before(&storage, Timestamp::from_seconds(time), start_after, limit)
+                .unwrap())
+        };
+        assert_eq!(maturing(50, None, None), Vec::<u64>::new());
+        assert_eq!(maturing(100, None, None), vec![1, 4]);
+        assert_eq!(maturing(250, None, None), vec![1, 4, 3]);
+        assert_eq!(
+            maturing(u64::MAX / 1_000_000_000, None, None),
+            vec![1, 4, 3, 0]
+        );
+
+        // Pagination continues after the last position of the previous page
+        assert_eq!(maturing(300, None, Some(2)), vec![1, 4]);
+        assert_eq!(
+            maturing(300, Some((Timestamp::from_seconds(100), 4)), Some(2)),
+            vec![3, 0]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lockup")]
+    fn release_time_index_is_updated() {
+        let mut storage = MockStorage::new();
+        let id = save_position(
+            &mut storage,
+            Expiration::AtTime(Timestamp::from_seconds(100)),
+        );
+        let mut position = POSITIONS.load(&storage, id).unwrap();
+        position.release_at = Expiration::AtTime(Timestamp::from_seconds(500));
+        POSITIONS.save(&mut storage, &position).unwrap();
+
+        let maturing = POSITIONS
+            .maturing_before(&storage, Timestamp::from_seconds(100), None, None)
+            .unwrap();
+        assert!(maturing.is_empty());
+
+        POSITIONS.remove(&mut storage, id).unwrap();
+        let maturing = POSITIONS
+            .maturing_before(&storage, Timestamp::from_seconds(500), None, None)
+            .unwrap();
+        assert!(maturing.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "keeper")]
+    fn keeper_bounties_accrue_and_are_claimed() {
+        use cosmwasm_std::coin;
+        use cosmwasm_std::testing::{mock_env, MockStorage};
+
+        const REWARDS: KeeperRewards = KeeperRewards::new("rewards", "rewards__last_paid");
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        let keeper = Addr::unchecked("keeper");
+
+        assert_eq!(REWARDS.last_paid(&storage, 0, &keeper).unwrap(), None);
+        REWARDS
+            .pay_bounty(&mut storage, 0, &keeper, &[coin(10, "uatom")], &block)
+            .unwrap();
+        let accrued = REWARDS
+            .pay_bounty(
+                &mut storage,
+                1,
+                &keeper,
+                &[coin(5, "uatom"), coin(1, "uosmo")],
+                &block,
+            )
+            .unwrap();
+        assert_eq!(accrued, vec![coin(15, "uatom"), coin(1, "uosmo")]);
+        assert_eq!(
+            REWARDS.last_paid(&storage, 0, &keeper).unwrap(),
+            Some(KeeperJobExecution {
+                height: block.height,
+                time: block.time,
+            })
+        );
+
+        assert_eq!(REWARDS.claim(&mut storage, &keeper).unwrap(), accrued);
+        assert!(REWARDS.load(&storage, &keeper).unwrap().is_empty());
+        assert!(REWARDS.last_paid(&storage, 1, &keeper).unwrap().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "keeper")]
+    fn keeper_whitelists_are_stored_per_job() {
+        use cosmwasm_std::testing::MockStorage;
+
+        const WHITELISTS: KeeperWhitelists =
+            KeeperWhitelists::new("whitelists", "whitelists__global");
+        let mut storage = MockStorage::new();
+        let addrs = |names: &[&str]| -> Vec<Addr> {
+            names.iter().map(|name| Addr::unchecked(*name)).collect()
+        };
+
+        WHITELISTS
+            .update(
+                &mut storage,
+                Some(1),
+                &addrs(&["c", "a", "b"]),
+                &addrs(&["c"]),
+            )
+            .unwrap();
+        WHITELISTS
+            .update(&mut storage, Some(2), &addrs(&["d"]), &[])
+            .unwrap();
+        WHITELISTS
+            .update(&mut storage, None, &addrs(&["global"]), &[])
+            .unwrap();
+
+        assert_eq!(
+            WHITELISTS.range(&storage, Some(1), None, None).unwrap(),
+            addrs(&["a", "b"])
+        );
+        assert_eq!(
+            WHITELISTS
+                .range(&storage, Some(1), Some(&Addr::unchecked("a")), Some(1))
+                .unwrap(),
+            addrs(&["b"])
+        );
+        assert_eq!(
+            WHITELISTS.range(&storage, None, None, None).unwrap(),
+            addrs(&["global"])
+        );
+
+        let keeper = Addr::unchecked("a");
+        assert!(WHITELISTS.is_whitelisted(&storage, 1, &keeper));
+        assert!(!WHITELISTS.is_whitelisted(&storage, 2, &keeper));
+        assert!(WHITELISTS.is_whitelisted(&storage, 2, &Addr::unchecked("global")));
+
+        WHITELISTS.remove_job(&mut storage, 1).unwrap();
+        assert!(WHITELISTS
+            .range(&storage, Some(1), None, None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            WHITELISTS.range(&storage, Some(2), None, None).unwrap(),
+            addrs(&["d"])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "analytics")]
+    fn exchange_rate_snapshots_are_a_ring_buffer() {
+        use cosmwasm_std::testing::MockStorage;
+
+        const SNAPSHOTS: ExchangeRateSnapshots =
+            ExchangeRateSnapshots::new("snapshots", "snapshots__len", "snapshots__config");
+        let mut storage = MockStorage::new();
+        let snapshot = |seconds: u64, exchange_rate: u64| ExchangeRateSnapshot {
+            time: Timestamp::from_seconds(seconds),
+            exchange_rate: Decimal::percent(exchange_rate),
+            total_assets: Uint128::zero(),
+        };
+        let times = |snapshots: Vec<ExchangeRateSnapshot>| -> Vec<u64> {
+            snapshots.iter().map(|s| s.time.seconds()).collect()
+        };
+
+        // The configuration must be set before taking snapshots
+        SNAPSHOTS.take(&mut storage, &snapshot(0, 100)).unwrap_err();
+        SNAPSHOTS
+            .update_config(
+                &mut storage,
+                SnapshotConfig {
+                    interval_seconds: 10,
+                    capacity: 0,
+                },
+            )
+            .unwrap_err();
+        SNAPSHOTS
+            .update_config(
+                &mut storage,
+                SnapshotConfig {
+                    interval_seconds: 10,
+                    capacity: 3,
+                },
+            )
+            .unwrap();
+
+        SNAPSHOTS.take(&mut storage, &snapshot(0, 100)).unwrap();
+        SNAPSHOTS.take(&mut storage, &snapshot(5, 101)).unwrap_err();
+        for (seconds, rate) in [(10, 101), (20, 102), (30, 103)] {
+            SNAPSHOTS
+                .take(&mut storage, &snapshot(seconds, rate))
+                .unwrap();
+        }
+
+        // The oldest snapshot was overwritten
+        assert_eq!(SNAPSHOTS.len(&storage).unwrap(), 3);
+        assert_eq!(
+            times(SNAPSHOTS.history(&storage, None, None).unwrap()),
+            vec![10, 20, 30]
+        );
+        assert_eq!(
+            times(
+                SNAPSHOTS
+                    .history(&storage, Some(Timestamp::from_seconds(10)), Some(1))
+                    .unwrap()
+            ),
+            vec![20]
+        );
+        SNAPSHOTS
+            .at(&storage, Timestamp::from_seconds(5))
+            .unwrap_err();
+        assert_eq!(
+            SNAPSHOTS.at(&storage, Timestamp::from_seconds(25)).unwrap(),
+            snapshot(20, 102)
+        );
+        assert_eq!(
+            SNAPSHOTS.realized_apr(&storage, 20).unwrap(),
+            apr_from_snapshots(&snapshot(10, 101), &snapshot(30, 103)).unwrap()
+        );
+
+        // Lowering the capacity drops the oldest snapshots
+        SNAPSHOTS
+            .update_config(
+                &mut storage,
+                SnapshotConfig {
+                    interval_seconds: 10,
+                    capacity: 1,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            times(SNAPSHOTS.history(&storage, None, None).unwrap()),
+            vec![30]
+        );
+        assert_eq!(SNAPSHOTS.latest(&storage).unwrap(), Some(snapshot(30, 103)));
+    }
+}
@@ -0,0 +1,132 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, StdResult, Uint128, WasmMsg};
+
+use super::{ExtensionExecuteMsg, VaultStandardExecuteMsg};
+
+/// The version 0.5 ExecuteMsg variants for vaults that enable the Lockup
+/// extension. Same as [`crate::extensions::lockup::LockupExecuteMsg`], but
+/// without the deprecated `amount` field of `Unlock`.
+#[cw_serde]
+pub enum LockupExecuteMsg {
+    /// Unlock is called to initiate unlocking a locked position held by the
+    /// vault.
+    /// The caller must pass the native vault tokens in the funds field.
+    /// Emits an event with type `UNLOCKING_POSITION_CREATED_EVENT_TYPE` with
+    /// an attribute with key `UNLOCKING_POSITION_ATTR_KEY` containing an u64
+    /// lockup_id.
+    #[serde(deserialize_with = "super::tier_with_legacy_amount")]
+    Unlock {
+        /// The ID of the lockup tier to unlock with, as returned by the
+        /// `LockupTiers` query. If not set, the default lockup duration of the
+        /// vault is used.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tier: Option<u64>,
+    },
+
+    /// EmergencyUnlock is called to initiate unlocking a locked position held
+    /// by the vault.
+    /// This call should simply unlock `amount` of vault tokens, without
+    /// performing any other side effects that might cause the transaction
+    /// to fail. Such as for example compoundning rewards for an LP
+    /// position.
+    EmergencyUnlock {
+        /// The amount of vault tokens to unlock.
+        amount: Uint128,
+    },
+
+    /// Withdraw an unlocking position that has finished unlocking.
+    WithdrawUnlocked {
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+        /// The ID of the expired lockup to withdraw from.
+        lockup_id: u64,
+    },
+
+    /// Withdraw all unlocking positions of the caller that have finished
+    /// unlocking.
+    WithdrawAllUnlocked {
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+
+    /// Withdraw several unlocking positions that have finished unlocking.
+    WithdrawUnlockedBatch {
+        /// The IDs of the expired lockups to withdraw from.
+        lockup_ids: Vec<u64>,
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+
+    /// Transfer an unlocking position owned by the caller to `recipient`. The
+    /// position keeps its ID, amount and `release_at`.
+    /// Emits an event with type `UNLOCKING_POSITION_TRANSFERRED_EVENT_TYPE`.
+    /// Not supported by vaults that enable the LockupNft extension, in which
+    /// the owner of a position is the owner of its cw721 token and positions
+    /// are transferred by transferring the token.
+    TransferUnlockingPosition {
+        /// The ID of the unlocking position to transfer.
+        lockup_id: u64,
+        /// The address which should become the owner of the position.
+        recipient: String,
+    },
+
+    /// Split `amount` base tokens off an unlocking position owned by the
+    /// caller into a new position, also owned by the caller, with the same
+    /// `release_at`.
+    /// Emits an event with type `UNLOCKING_POSITION_SPLIT_EVENT_TYPE`.
+    SplitUnlockingPosition {
+        /// The ID of the unlocking position to split.
+        lockup_id: u64,
+        /// The amount of base tokens to move to the new position.
+        amount: Uint128,
+    },
+
+    /// Merge unlocking positions owned by the caller into the first position
+    /// in `ids`, removing the others. The merged position is released at the
+    /// latest `release_at` of the merged positions.
+    /// Emits an event with type `UNLOCKING_POSITIONS_MERGED_EVENT_TYPE`.
+    MergeUnlockingPositions {
+        /// The IDs of the unlocking positions to merge.
+        ids: Vec<u64>,
+    },
+
+    /// Cancel an unlocking position owned by the caller, removing it and
+    /// minting vault tokens to the caller in return.
+    /// Emits an event with type `UNLOCK_CANCELLED_EVENT_TYPE`.
+    CancelUnlock {
+        /// The ID of the unlocking position to cancel.
+        lockup_id: u64,
+    },
+
+    /// Withdraw an unlocking position owned by the caller before it has
+    /// finished unlocking, in exchange for a penalty.
+    /// Emits an event with type `EARLY_WITHDRAW_EVENT_TYPE`.
+    EarlyWithdraw {
+        /// The ID of the unlocking position to withdraw.
+        lockup_id: u64,
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+}
+
+impl LockupExecuteMsg {
+    /// Convert a [`LockupExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&VaultStandardExecuteMsg::VaultExtension(
+                ExtensionExecuteMsg::Lockup(self),
+            ))?,
+            funds,
+        }
+        .into())
+    }
+}
@@ -0,0 +1,252 @@
+//! In version 0.5 of the vault standard the deprecated `amount` fields of
+//! `Deposit`, `Redeem`, `Unlock` and `ForceRedeem` are removed, and the amount
+//! is instead read from the funds sent with the message, e.g. with
+//! [`funds_amount`](crate::v0_5::funds_amount).
+//!
+//! To let vaults upgrade without breaking existing callers, the messages in
+//! this module are serialized without the removed fields, but can be
+//! deserialized from both the old and the new format. A vault that uses these
+//! messages in its entrypoints therefore accepts messages from both old and new
+//! clients. The value of the `amount` field in old messages is ignored.
+//!
+//! Clients should keep sending messages from the crate root to vaults that
+//! still use an older version of the vault standard, see
+//! [`check_compatibility`](crate::compatibility::check_compatibility).
+
+#[cfg(feature = "force-unlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "force-unlock")))]
+pub mod force_unlock;
+#[cfg(feature = "lockup")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lockup")))]
+pub mod lockup;
+
+#[cfg(feature = "analytics")]
+use crate::extensions::analytics::AnalyticsExecuteMsg;
+#[cfg(feature = "harvest")]
+use crate::extensions::harvest::HarvestExecuteMsg;
+#[cfg(feature = "keeper")]
+use crate::extensions::keeper::KeeperExecuteMsg;
+#[cfg(feature = "strategies")]
+use crate::extensions::strategies::StrategiesExecuteMsg;
+#[cfg(feature = "force-unlock")]
+use force_unlock::ForceUnlockExecuteMsg;
+#[cfg(feature = "lockup")]
+use lockup::LockupExecuteMsg;
+
+use crate::validation::{one_coin_of, PaymentError};
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, MessageInfo, StdResult, Uint128, WasmMsg};
+use serde::{Deserialize, Deserializer};
+
+/// The version of the vault standard that vaults using the messages of this
+/// module should report in the `VaultStandardInfo` query, e.g. by returning it
+/// from [`VaultImpl::vault_standard_version`](crate::framework::VaultImpl::vault_standard_version).
+pub const VERSION: &str = crate::compatibility::NEWEST_SUPPORTED_VERSION;
+
+/// The version 0.5 ExecuteMsg variants that all vaults must implement. Same as
+/// [`crate::VaultStandardExecuteMsg`], but without the deprecated `amount`
+/// fields.
+#[cw_serde]
+pub enum VaultStandardExecuteMsg<T = ExtensionExecuteMsg> {
+    /// Called to deposit into the vault. Native assets are passed in the funds
+    /// parameter.
+    #[serde(deserialize_with = "recipient_with_legacy_amount")]
+    Deposit {
+        /// The optional recipient of the vault token. If not set, the caller
+        /// address will be used instead.
+        recipient: Option<String>,
+    },
+
+    /// Called to redeem vault tokens and receive assets back from the vault.
+    /// The native vault token must be passed in the funds parameter, unless the
+    /// lockup extension is called, in which case the vault token has already
+    /// been passed to ExecuteMsg::Unlock.
+    #[serde(deserialize_with = "recipient_with_legacy_amount")]
+    Redeem {
+        /// An optional field containing which address should receive the
+        /// withdrawn base tokens. If not set, the caller address will be
+        /// used instead.
+        recipient: Option<String>,
+    },
+
+    /// Called to execute functionality of any enabled extensions.
+    VaultExtension(T),
+}
+
+impl<T> VaultStandardExecuteMsg<T>
+where
+    T: serde::Serialize,
+{
+    /// Convert a [`VaultStandardExecuteMsg`] into a [`CosmosMsg`].
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&self)?,
+            funds,
+        }
+        .into())
+    }
+}
+
+/// Contains the version 0.5 ExecuteMsgs of all enabled extensions. Same as
+/// [`crate::ExtensionExecuteMsg`], but using the version 0.5 messages of
+/// extensions that had deprecated fields.
+#[cw_serde]
+pub enum ExtensionExecuteMsg {
+    #[cfg(feature = "keeper")]
+    Keeper(KeeperExecuteMsg),
+    #[cfg(feature = "lockup")]
+    Lockup(LockupExecuteMsg),
+    #[cfg(feature = "force-unlock")]
+    ForceUnlock(ForceUnlockExecuteMsg),
+    #[cfg(feature = "strategies")]
+    Strategies(StrategiesExecuteMsg),
+    #[cfg(feature = "harvest")]
+    Harvest(HarvestExecuteMsg),
+    #[cfg(feature = "analytics")]
+    Analytics(AnalyticsExecuteMsg),
+}
+
+/// Returns the amount of `denom` sent with the message. Returns an error unless
+/// exactly one coin, of denom `denom`, was sent.
+pub fn funds_amount(info: &MessageInfo, denom: &str) -> Result<Uint128, PaymentError> {
+    one_coin_of(info, denom)
+}
+
+/// Deserializes the fields of a variant that only has a `recipient` field, but
+/// previously also had the now removed `amount` field.
+fn recipient_with_legacy_amount<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Fields {
+        recipient: Option<String>,
+        #[serde(default, rename = "amount")]
+        _amount: Option<Uint128>,
+    }
+
+    Fields::deserialize(deserializer).map(|fields| fields.recipient)
+}
+
+/// Deserializes the fields of `Unlock`, which previously also had the now
+/// removed `amount` field.
+#[cfg(feature = "lockup")]
+fn tier_with_legacy_amount<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Fields {
+        tier: Option<u64>,
+        #[serde(default, rename = "amount")]
+        _amount: Option<Uint128>,
+    }
+
+    Fields::deserialize(deserializer).map(|fields| fields.tier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{coin, from_json, to_json_string, Empty};
+
+    type ExecuteMsg = VaultStandardExecuteMsg<Empty>;
+
+    #[test]
+    fn deposit_and_redeem_are_serialized_without_amount() {
+        let deposit = ExecuteMsg::Deposit {
+            recipient: Some("addr".to_string()),
+        };
+        let redeem = ExecuteMsg::Redeem { recipient: None };
+        assert_eq!(
+            to_json_string(&deposit).unwrap(),
+            r#"{"deposit":{"recipient":"addr"}}"#
+        );
+        assert_eq!(
+            to_json_string(&redeem).unwrap(),
+            r#"{"redeem":{"recipient":null}}"#
+        );
+        assert_eq!(
+            from_json::<ExecuteMsg>(to_json_string(&deposit).unwrap()).unwrap(),
+            deposit
+        );
+        assert_eq!(
+            from_json::<ExecuteMsg>(to_json_string(&redeem).unwrap()).unwrap(),
+            redeem
+        );
+    }
+
+    #[test]
+    fn legacy_deposit_and_redeem_are_deserialized() {
+        assert_eq!(
+            from_json::<ExecuteMsg>(br#"{"deposit":{"amount":"100","recipient":"addr"}}"#).unwrap(),
+            ExecuteMsg::Deposit {
+                recipient: Some("addr".to_string())
+            }
+        );
+        assert_eq!(
+            from_json::<ExecuteMsg>(br#"{"redeem":{"amount":"100","recipient":null}}"#).unwrap(),
+            ExecuteMsg::Redeem { recipient: None }
+        );
+        // Messages serialized by the crate root
+        let legacy = crate::VaultStandardExecuteMsg::<Empty>::Redeem {
+            amount: Uint128::new(100),
+            recipient: Some("addr".to_string()),
+        };
+        assert_eq!(
+            from_json::<ExecuteMsg>(to_json_string(&legacy).unwrap()).unwrap(),
+            ExecuteMsg::Redeem {
+                recipient: Some("addr".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn deposit_and_redeem_without_fields_are_deserialized() {
+        assert_eq!(
+            from_json::<ExecuteMsg>(br#"{"deposit":{}}"#).unwrap(),
+            ExecuteMsg::Deposit { recipient: None }
+        );
+        assert_eq!(
+            from_json::<ExecuteMsg>(br#"{"redeem":{}}"#).unwrap(),
+            ExecuteMsg::Redeem { recipient: None }
+        );
+    }
+
+    #[test]
+    fn unknown_fields_and_invalid_amounts_are_rejected() {
+        from_json::<ExecuteMsg>(br#"{"deposit":{"recipient":null,"other":1}}"#).unwrap_err();
+        from_json::<ExecuteMsg>(br#"{"redeem":{"amount":"abc"}}"#).unwrap_err();
+    }
+
+    #[test]
+    #[cfg(feature = "lockup")]
+    fn unlock_without_tier_is_serialized_without_fields() {
+        let unlock = LockupExecuteMsg::Unlock { tier: None };
+        assert_eq!(to_json_string(&unlock).unwrap(), r#"{"unlock":{}}"#);
+        assert_eq!(
+            from_json::<LockupExecuteMsg>(r#"{"unlock":{"amount":"1"}}"#).unwrap(),
+            unlock
+        );
+    }
+
+    #[test]
+    fn funds_amount_returns_typed_errors() {
+        let info = mock_info("sender", &[coin(100, "base")]);
+        assert_eq!(funds_amount(&info, "base").unwrap(), Uint128::new(100));
+        assert_eq!(
+            funds_amount(&info, "vault").unwrap_err(),
+            PaymentError::MissingDenom("vault".to_string())
+        );
+        assert_eq!(
+            funds_amount(&mock_info("sender", &[]), "base").unwrap_err(),
+            PaymentError::NoFunds {}
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use cosmwasm_std::{Addr, Api, MessageInfo, StdResult, Uint128};
+
+pub use cw_utils::PaymentError;
+
+/// Returns the amount of `denom` sent with the message. Returns an error unless
+/// exactly one coin, of denom `denom`, was sent.
+pub fn one_coin_of(info: &MessageInfo, denom: &str) -> Result<Uint128, PaymentError> {
+    cw_utils::must_pay(info, denom)
+}
+
+/// Returns the amount of base tokens sent with the message. Should be used to
+/// read the deposited amount in `Deposit`. Returns an error unless exactly one
+/// coin, of denom `base_token`, was sent.
+pub fn must_pay_base_tokens(info: &MessageInfo, base_token: &str) -> Result<Uint128, PaymentError> {
+    one_coin_of(info, base_token)
+}
+
+/// Returns the amount of vault tokens sent with the message. Should be used to
+/// read the amount of vault tokens in `Redeem`, `Unlock` and `ForceRedeem`.
+/// Returns an error unless exactly one coin, of denom `vault_token`, was sent.
+pub fn must_pay_vault_tokens(
+    info: &MessageInfo,
+    vault_token: &str,
+) -> Result<Uint128, PaymentError> {
+    one_coin_of(info, vault_token)
+}
+
+/// Returns an error if any funds were sent with the message. Should be used
+/// for all messages that do not expect any funds, such as `WithdrawUnlocked`.
+pub fn nonpayable(info: &MessageInfo) -> Result<(), PaymentError> {
+    cw_utils::nonpayable(info)
+}
+
+/// Returns the validated address of `recipient`, or the address of the sender
+/// of the message if `recipient` is not set.
+pub fn resolve_recipient(
+    api: &dyn Api,
+    info: &MessageInfo,
+    recipient: Option<String>,
+) -> StdResult<Addr> {
+    match recipient {
+        Some(recipient) => api.addr_validate(&recipient),
+        None => Ok(info.sender.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+    use cosmwasm_std::{coin, Coin};
+
+    fn info(funds: &[Coin]) -> MessageInfo {
+        mock_info("sender", funds)
+    }
+
+    #[test]
+    fn one_coin_of_accepts_exactly_one_coin_of_denom() {
+        assert_eq!(
+            one_coin_of(&info(&[coin(100, "base")]), "base").unwrap(),
+            Uint128::new(100)
+        );
+    }
+
+    #[test]
+    fn one_coin_of_rejects_other_funds() {
+        assert_eq!(
+            one_coin_of(&info(&[]), "base").unwrap_err(),
+            PaymentError::NoFunds {}
+        );
+        assert_eq!(
+            one_coin_of(&info(&[coin(100, "other")]), "base").unwrap_err(),
+            PaymentError::MissingDenom("base".to_string())
+        );
+        assert_eq!(
+            one_coin_of(&info(&[coin(100, "base"), coin(1, "other")]), "base").unwrap_err(),
+            PaymentError::MultipleDenoms {}
+        );
+    }
+
+    #[test]
+    fn must_pay_base_and_vault_tokens_check_their_denom() {
+        let funds = [coin(100, "base")];
+        assert_eq!(
+            must_pay_base_tokens(&info(&funds), "base").unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            must_pay_vault_tokens(&info(&funds), "vault").unwrap_err(),
+            PaymentError::MissingDenom("vault".to_string())
+        );
+        assert_eq!(
+            must_pay_vault_tokens(&info(&[coin(5, "vault")]), "vault").unwrap(),
+            Uint128::new(5)
+        );
+        assert_eq!(
+            must_pay_base_tokens(&info(&[]), "base").unwrap_err(),
+            PaymentError::NoFunds {}
+        );
+    }
+
+    #[test]
+    fn nonpayable_rejects_funds() {
+        nonpayable(&info(&[])).unwrap();
+        assert_eq!(
+            nonpayable(&info(&[coin(1, "base")])).unwrap_err(),
+            PaymentError::NonPayable {}
+        );
+    }
+
+    #[test]
+    fn resolve_recipient_defaults_to_sender() {
+        let deps = mock_dependencies();
+        assert_eq!(
+            resolve_recipient(&deps.api, &info(&[]), None).unwrap(),
+            Addr::unchecked("sender")
+        );
+        assert_eq!(
+            resolve_recipient(&deps.api, &info(&[]), Some("recipient".to_string())).unwrap(),
+            Addr::unchecked("recipient")
+        );
+        resolve_recipient(&deps.api, &info(&[]), Some("".to_string())).unwrap_err();
+    }
+}
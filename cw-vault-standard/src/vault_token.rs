@@ -0,0 +1,545 @@
+use cosmwasm_schema::cw_serde;
+use std::cell::Cell;
+
+use cosmwasm_std::{
+    coins, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, QuerierWrapper, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, TokenInfoResponse};
+
+/// The type URL prefix of the tokenfactory module messages on Osmosis.
+pub const OSMOSIS_TOKENFACTORY_TYPE_URL_PREFIX: &str = "/osmosis.tokenfactory.v1beta1";
+
+/// Returns the denom of a tokenfactory token created by `creator` with
+/// subdenom `subdenom`.
+pub fn tokenfactory_denom(creator: &str, subdenom: &str) -> String {
+    format!("factory/{creator}/{subdenom}")
+}
+
+/// Mints, burns and queries the supply of the vault token, so that vault code
+/// does not need to know how the vault token is implemented on the chain it is
+/// deployed to.
+///
+/// Implemented by [`OsmosisDenom`], [`TokenFactoryDenom`], [`Cw20VaultToken`]
+/// and [`VaultTokenKind`], and by [`MockVaultToken`] for unit tests. Vaults can
+/// also implement it for their own types.
+pub trait VaultToken {
+    /// Returns the denom of the vault token, as returned by the `Info` query.
+    /// For CW20 vault tokens this is the address of the CW20 contract.
+    fn denom(&self) -> String;
+
+    /// Returns the messages that create the vault token. Should be dispatched
+    /// once, when the vault is instantiated.
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>>;
+
+    /// Returns a message that mints `amount` vault tokens to `recipient`.
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg>;
+
+    /// Returns a message that burns `amount` vault tokens held by the vault.
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg>;
+
+    /// Queries the total supply of the vault token.
+    fn query_total_supply(&self, querier: &QuerierWrapper) -> StdResult<Uint128>;
+}
+
+/// A vault token created with the tokenfactory module of Osmosis, using the
+/// message types of `osmosis-std`.
+#[cfg(feature = "osmosis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "osmosis")))]
+#[cw_serde]
+pub struct OsmosisDenom {
+    /// The address of the vault, which creates and administers the denom.
+    pub creator: String,
+    /// The subdenom of the vault token.
+    pub subdenom: String,
+}
+
+#[cfg(feature = "osmosis")]
+impl OsmosisDenom {
+    /// Create a new OsmosisDenom.
+    pub fn new(creator: String, subdenom: String) -> Self {
+        Self { creator, subdenom }
+    }
+
+    fn coin(&self, amount: Uint128) -> osmosis_std::types::cosmos::base::v1beta1::Coin {
+        osmosis_std::types::cosmos::base::v1beta1::Coin {
+            denom: self.denom(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "osmosis")]
+impl VaultToken for OsmosisDenom {
+    fn denom(&self) -> String {
+        tokenfactory_denom(&self.creator, &self.subdenom)
+    }
+
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>> {
+        use osmosis_std::types::osmosis::tokenfactory::v1beta1::MsgCreateDenom;
+
+        Ok(vec![MsgCreateDenom {
+            sender: self.creator.clone(),
+            subdenom: self.subdenom.clone(),
+        }
+        .into()])
+    }
+
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        use osmosis_std::types::osmosis::tokenfactory::v1beta1::MsgMint;
+
+        Ok(MsgMint {
+            sender: self.creator.clone(),
+            amount: Some(self.coin(amount)),
+            mint_to_address: recipient.to_string(),
+        }
+        .into())
+    }
+
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        use osmosis_std::types::osmosis::tokenfactory::v1beta1::MsgBurn;
+
+        Ok(MsgBurn {
+            sender: self.creator.clone(),
+            amount: Some(self.coin(amount)),
+            burn_from_address: self.creator.clone(),
+        }
+        .into())
+    }
+
+    fn query_total_supply(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        Ok(querier.query_supply(self.denom())?.amount)
+    }
+}
+
+/// A vault token created with a tokenfactory module that uses the same messages
+/// as the Osmosis tokenfactory module, under a different type URL prefix. This
+/// is the case for most Cosmos SDK chains that ship a tokenfactory module.
+#[cw_serde]
+pub struct TokenFactoryDenom {
+    /// The type URL prefix of the tokenfactory messages on the chain, e.g.
+    /// `/osmosis.tokenfactory.v1beta1`.
+    pub type_url_prefix: String,
+    /// The address of the vault, which creates and administers the denom.
+    pub creator: String,
+    /// The subdenom of the vault token.
+    pub subdenom: String,
+}
+
+impl TokenFactoryDenom {
+    /// Create a new TokenFactoryDenom.
+    pub fn new(type_url_prefix: String, creator: String, subdenom: String) -> Self {
+        Self {
+            type_url_prefix,
+            creator,
+            subdenom,
+        }
+    }
+
+    fn stargate_msg(&self, msg_name: &str, value: Vec<u8>) -> CosmosMsg {
+        CosmosMsg::Stargate {
+            type_url: format!("{}.{msg_name}", self.type_url_prefix),
+            value: Binary::from(value),
+        }
+    }
+
+    /// Encodes a `cosmos.base.v1beta1.Coin` of `amount` vault tokens.
+    fn encode_coin(&self, amount: Uint128) -> Vec<u8> {
+        let mut buf = vec![];
+        proto::encode_string(&mut buf, 1, &self.denom());
+        proto::encode_string(&mut buf, 2, &amount.to_string());
+        buf
+    }
+
+    /// Encodes a `MsgMint` or `MsgBurn`, which share the same layout.
+    fn encode_supply_change(&self, amount: Uint128, address: &str) -> Vec<u8> {
+        let mut buf = vec![];
+        proto::encode_string(&mut buf, 1, &self.creator);
+        proto::encode_bytes(&mut buf, 2, &self.encode_coin(amount));
+        proto::encode_string(&mut buf, 3, address);
+        buf
+    }
+}
+
+impl VaultToken for TokenFactoryDenom {
+    fn denom(&self) -> String {
+        tokenfactory_denom(&self.creator, &self.subdenom)
+    }
+
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>> {
+        let mut buf = vec![];
+        proto::encode_string(&mut buf, 1, &self.creator);
+        proto::encode_string(&mut buf, 2, &self.subdenom);
+
+        Ok(vec![self.stargate_msg("MsgCreateDenom", buf)])
+    }
+
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(self.stargate_msg(
+            "MsgMint",
+            self.encode_supply_change(amount, recipient.as_str()),
+        ))
+    }
+
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(self.stargate_msg("MsgBurn", self.encode_supply_change(amount, &self.creator)))
+    }
+
+    fn query_total_supply(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        Ok(querier.query_supply(self.denom())?.amount)
+    }
+}
+
+/// A vault token implemented as a CW20 contract.
+///
+/// The CW20 contract must be instantiated separately, with the vault as its
+/// only minter, so [`VaultToken::instantiate`] returns no messages.
+#[cw_serde]
+pub struct Cw20VaultToken {
+    /// The address of the CW20 contract.
+    pub address: Addr,
+}
+
+impl Cw20VaultToken {
+    /// Create a new Cw20VaultToken.
+    pub fn new(address: Addr) -> Self {
+        Self { address }
+    }
+
+    fn execute(&self, msg: &Cw20ExecuteMsg) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.address.to_string(),
+            msg: to_json_binary(msg)?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+impl VaultToken for Cw20VaultToken {
+    fn denom(&self) -> String {
+        self.address.to_string()
+    }
+
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>> {
+        Ok(vec![])
+    }
+
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.execute(&Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount,
+        })
+    }
+
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.execute(&Cw20ExecuteMsg::Burn { amount })
+    }
+
+    fn query_total_supply(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        let info: TokenInfoResponse =
+            querier.query_wasm_smart(&self.address, &Cw20QueryMsg::TokenInfo {})?;
+        Ok(info.total_supply)
+    }
+}
+
+/// Any of the vault token implementations of this crate. Can be stored in the
+/// config of a vault that supports several chains.
+#[cw_serde]
+pub enum VaultTokenKind {
+    /// A token created with the tokenfactory module of Osmosis.
+    #[cfg(feature = "osmosis")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "osmosis")))]
+    Osmosis(OsmosisDenom),
+    /// A token created with a generic tokenfactory module.
+    TokenFactory(TokenFactoryDenom),
+    /// A CW20 token.
+    Cw20(Cw20VaultToken),
+}
+
+impl VaultTokenKind {
+    fn inner(&self) -> &dyn VaultToken {
+        match self {
+            #[cfg(feature = "osmosis")]
+            VaultTokenKind::Osmosis(token) => token,
+            VaultTokenKind::TokenFactory(token) => token,
+            VaultTokenKind::Cw20(token) => token,
+        }
+    }
+}
+
+impl VaultToken for VaultTokenKind {
+    fn denom(&self) -> String {
+        self.inner().denom()
+    }
+
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>> {
+        self.inner().instantiate()
+    }
+
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.inner().mint(recipient, amount)
+    }
+
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.inner().burn(amount)
+    }
+
+    fn query_total_supply(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        self.inner().query_total_supply(querier)
+    }
+}
+
+/// A vault token for unit tests of vault code, which keeps track of its supply
+/// in memory instead of on chain, so that the supply changes of a vault can be
+/// tested without a tokenfactory module or CW20 contract.
+///
+/// Minting returns a `BankMsg::Send` of the minted tokens to the recipient and
+/// burning a `BankMsg::Burn`, so that the returned messages can be asserted
+/// on. Neither message is meant to be executed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockVaultToken {
+    /// The denom of the vault token.
+    pub denom: String,
+    supply: Cell<Uint128>,
+}
+
+impl MockVaultToken {
+    /// Create a new MockVaultToken with a supply of zero.
+    pub fn new(denom: impl Into<String>) -> Self {
+        Self {
+            denom: denom.into(),
+            supply: Cell::default(),
+        }
+    }
+
+    /// Returns the supply of the vault token.
+    pub fn supply(&self) -> Uint128 {
+        self.supply.get()
+    }
+}
+
+impl VaultToken for MockVaultToken {
+    fn denom(&self) -> String {
+        self.denom.clone()
+    }
+
+    fn instantiate(&self) -> StdResult<Vec<CosmosMsg>> {
+        Ok(vec![])
+    }
+
+    fn mint(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.supply.set(self.supply.get().checked_add(amount)?);
+        Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), &self.denom),
+        }
+        .into())
+    }
+
+    fn burn(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        self.supply.set(self.supply.get().checked_sub(amount)?);
+        Ok(BankMsg::Burn {
+            amount: coins(amount.u128(), &self.denom),
+        }
+        .into())
+    }
+
+    fn query_total_supply(&self, _querier: &QuerierWrapper) -> StdResult<Uint128> {
+        Ok(self.supply.get())
+    }
+}
+
+/// Minimal protobuf encoding of the string and message fields used by the
+/// tokenfactory messages.
+mod proto {
+    const WIRE_TYPE_LEN: u64 = 2;
+
+    pub fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buf.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        buf.push(value as u8);
+    }
+
+    /// Encodes a length-delimited field. Empty fields are omitted, as in proto3.
+    pub fn encode_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        encode_varint(buf, (u64::from(field) << 3) | WIRE_TYPE_LEN);
+        encode_varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    pub fn encode_string(buf: &mut Vec<u8>, field: u32, s: &str) {
+        encode_bytes(buf, field, s.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{from_json, Empty};
+
+    fn tokenfactory_denom() -> TokenFactoryDenom {
+        TokenFactoryDenom::new(
+            OSMOSIS_TOKENFACTORY_TYPE_URL_PREFIX.to_string(),
+            "vault".to_string(),
+            "share".to_string(),
+        )
+    }
+
+    #[test]
+    fn varints_are_encoded_in_groups_of_seven_bits() {
+        let varint = |value| {
+            let mut buf = vec![];
+            proto::encode_varint(&mut buf, value);
+            buf
+        };
+        assert_eq!(varint(0), vec![0]);
+        assert_eq!(varint(127), vec![0x7f]);
+        assert_eq!(varint(128), vec![0x80, 0x01]);
+        assert_eq!(varint(300), vec![0xac, 0x02]);
+        assert_eq!(varint(u64::MAX).len(), 10);
+    }
+
+    #[test]
+    fn fields_longer_than_127_bytes_have_multi_byte_lengths() {
+        let mut buf = vec![];
+        proto::encode_string(&mut buf, 2, &"a".repeat(200));
+        assert_eq!(buf[..3], [0x12, 0xc8, 0x01]);
+        assert_eq!(buf.len(), 203);
+
+        let mut buf = vec![];
+        proto::encode_string(&mut buf, 1, "");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn tokenfactory_messages_are_encoded() {
+        let token = tokenfactory_denom();
+        assert_eq!(token.denom(), "factory/vault/share");
+        assert_eq!(
+            token.instantiate().unwrap(),
+            vec![CosmosMsg::Stargate {
+                type_url: "/osmosis.tokenfactory.v1beta1.MsgCreateDenom".to_string(),
+                value: Binary::from(b"\x0a\x05vault\x12\x05share".as_slice()),
+            }]
+        );
+        let CosmosMsg::Stargate { type_url, value } = token.burn(Uint128::new(100)).unwrap() else {
+            panic!("expected a stargate message");
+        };
+        assert_eq!(type_url, "/osmosis.tokenfactory.v1beta1.MsgBurn");
+        assert_eq!(
+            value.as_slice(),
+            b"\x0a\x05vault\x12\x1a\x0a\x13factory/vault/share\x12\x03100\x1a\x05vault"
+        );
+    }
+
+    #[cfg(feature = "osmosis")]
+    #[test]
+    fn tokenfactory_messages_match_osmosis_std() {
+        let subdenoms = ["share".to_string(), "s".repeat(200)];
+        for subdenom in subdenoms {
+            let creator = "osmo1vault".to_string();
+            let recipient = Addr::unchecked("r".repeat(150));
+            let amount = Uint128::MAX;
+            let generic = TokenFactoryDenom::new(
+                OSMOSIS_TOKENFACTORY_TYPE_URL_PREFIX.to_string(),
+                creator.clone(),
+                subdenom.clone(),
+            );
+            let osmosis = OsmosisDenom::new(creator, subdenom);
+
+            assert_eq!(generic.denom(), osmosis.denom());
+            assert_eq!(
+                generic.instantiate().unwrap(),
+                osmosis.instantiate().unwrap()
+            );
+            assert_eq!(
+                generic.mint(&recipient, amount).unwrap(),
+                osmosis.mint(&recipient, amount).unwrap()
+            );
+            assert_eq!(generic.burn(amount).unwrap(), osmosis.burn(amount).unwrap());
+        }
+    }
+
+    #[test]
+    fn cw20_messages_are_sent_to_the_token_contract() {
+        let token = Cw20VaultToken::new(Addr::unchecked("cw20"));
+        assert_eq!(token.denom(), "cw20");
+        assert!(token.instantiate().unwrap().is_empty());
+
+        let CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds,
+        }) = token
+            .mint(&Addr::unchecked("user"), Uint128::new(5))
+            .unwrap()
+        else {
+            panic!("expected a wasm execute message");
+        };
+        assert_eq!(contract_addr, "cw20");
+        assert!(funds.is_empty());
+        assert_eq!(
+            from_json::<Cw20ExecuteMsg>(&msg).unwrap(),
+            Cw20ExecuteMsg::Mint {
+                recipient: "user".to_string(),
+                amount: Uint128::new(5),
+            }
+        );
+
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = token.burn(Uint128::new(3)).unwrap()
+        else {
+            panic!("expected a wasm execute message");
+        };
+        assert_eq!(
+            from_json::<Cw20ExecuteMsg>(&msg).unwrap(),
+            Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(3)
+            }
+        );
+    }
+
+    #[test]
+    fn vault_token_kind_delegates_to_the_inner_token() {
+        let token = tokenfactory_denom();
+        let kind = VaultTokenKind::TokenFactory(token.clone());
+        assert_eq!(kind.denom(), token.denom());
+        assert_eq!(
+            kind.burn(Uint128::one()).unwrap(),
+            token.burn(Uint128::one()).unwrap()
+        );
+    }
+
+    #[test]
+    fn mock_vault_token_tracks_supply() {
+        let token = MockVaultToken::new("vault");
+        let querier = MockQuerier::<Empty>::new(&[]);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+
+        assert_eq!(
+            token
+                .mint(&Addr::unchecked("user"), Uint128::new(10))
+                .unwrap(),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user".to_string(),
+                amount: coins(10, "vault"),
+            })
+        );
+        assert_eq!(
+            token.burn(Uint128::new(4)).unwrap(),
+            CosmosMsg::Bank(BankMsg::Burn {
+                amount: coins(4, "vault"),
+            })
+        );
+        assert_eq!(token.query_total_supply(&querier).unwrap(), Uint128::new(6));
+        token.burn(Uint128::new(7)).unwrap_err();
+        assert_eq!(token.supply(), Uint128::new(6));
+    }
+}
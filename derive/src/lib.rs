@@ -0,0 +1,376 @@
+//! # CosmWasm Vault Standard Derive
+//!
+//! Procedural macros for vaults implementing the [CosmWasm Vault
+//! Standard](https://docs.rs/cw-vault-standard). These macros are re-exported
+//! by `cw-vault-standard` when the `derive` feature is enabled, and should be
+//! used through that crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, parse_macro_input, Error, Ident, ItemStruct, Path, Result, Token};
+
+/// Generates the `ExtensionExecuteMsg` and `ExtensionQueryMsg` enums of a vault
+/// from the list of extensions it uses.
+///
+/// The attribute is placed on a unit struct and takes a comma separated list of
+/// extensions. Built-in extensions are given by their name, i.e. `lockup`,
+/// `lockup_nft`, `force_unlock`, `keeper`, `strategies`, `harvest` or
+/// `analytics`, and require the corresponding feature of `cw-vault-standard`
+/// to be enabled. Custom extensions are given as `name = MyExt`, in which case
+/// the `MyExtExecuteMsg` and `MyExtQueryMsg` enums must be in scope, or as
+/// `name(execute = Path, query = Path)` if the extension only has one of the
+/// two, or its enums are named differently.
+///
+/// ```ignore
+/// #[vault_extensions(lockup, force_unlock, my_ext = MyExt)]
+/// pub struct Extensions;
+///
+/// pub type ExecuteMsg = VaultStandardExecuteMsg<ExtensionExecuteMsg>;
+/// pub type QueryMsg = VaultStandardQueryMsg<ExtensionQueryMsg>;
+/// ```
+///
+/// For each extension, a variant named after the extension in UpperCamelCase
+/// is added to the enums, together with a `From` implementation wrapping the
+/// extension message into it. `ExtensionQueryMsg` also derives
+/// `QueryResponses`, using the responses of the nested extension messages. An
+/// enum without any variants is generated as an alias of `Empty` instead.
+///
+/// Finally, the struct gets an `extensions` function returning the
+/// `ExtensionInfo`s of the extensions, and a `vault_standard_info` function
+/// returning the `VaultStandardInfoResponse` of the vault. Built-in
+/// extensions use the canonical extension names and the version of the vault
+/// standard, while custom extensions use their name in kebab-case and the
+/// version of the vault crate.
+#[proc_macro_attribute]
+pub fn vault_extensions(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as ExtensionArgs);
+    let item = parse_macro_input!(item as ItemStruct);
+
+    expand(args, item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The parsed arguments of the `vault_extensions` attribute.
+struct ExtensionArgs(Vec<Extension>);
+
+/// A single extension used by the vault.
+struct Extension {
+    /// The name of the extension as given in the attribute.
+    ident: Ident,
+    /// The path of the extension's execute message enum, if it has one.
+    execute: Option<Path>,
+    /// The path of the extension's query message enum, if it has one.
+    query: Option<Path>,
+    /// The tokens evaluating to the `ExtensionInfo` of the extension.
+    info: TokenStream2,
+}
+
+impl Parse for ExtensionArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let exts = Punctuated::<Extension, Token![,]>::parse_terminated(input)?;
+
+        let mut names: Vec<String> = vec![];
+        for ext in &exts {
+            let name = ext.ident.to_string();
+            if names.contains(&name) {
+                return Err(Error::new(
+                    ext.ident.span(),
+                    format!("duplicate extension `{name}`"),
+                ));
+            }
+            names.push(name);
+        }
+
+        Ok(Self(exts.into_iter().collect()))
+    }
+}
+
+impl Parse for Extension {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident: Ident = input.parse()?;
+
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let prefix: Path = input.parse()?;
+            return Ok(Self::custom(
+                ident,
+                Some(with_suffix(&prefix, "ExecuteMsg")?),
+                Some(with_suffix(&prefix, "QueryMsg")?),
+            ));
+        }
+
+        if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let mut execute = None;
+            let mut query = None;
+            for (key, path) in Punctuated::<KeyPath, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .map(|kp| (kp.key, kp.path))
+            {
+                let slot = match key.to_string().as_str() {
+                    "execute" => &mut execute,
+                    "query" => &mut query,
+                    _ => {
+                        return Err(Error::new(
+                            key.span(),
+                            "expected `execute = Path` or `query = Path`",
+                        ))
+                    }
+                };
+                if slot.replace(path).is_some() {
+                    return Err(Error::new(key.span(), format!("duplicate `{key}`")));
+                }
+            }
+            if execute.is_none() && query.is_none() {
+                return Err(Error::new(
+                    ident.span(),
+                    "custom extension must have an execute or query message",
+                ));
+            }
+            return Ok(Self::custom(ident, execute, query));
+        }
+
+        Self::builtin(ident)
+    }
+}
+
+/// A `key = Path` pair inside the parentheses of a custom extension.
+struct KeyPath {
+    key: Ident,
+    path: Path,
+}
+
+impl Parse for KeyPath {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let path = input.parse()?;
+        Ok(Self { key, path })
+    }
+}
+
+impl Extension {
+    fn builtin(ident: Ident) -> Result<Self> {
+        let (module, prefix, name, has_execute, has_query) =
+            match ident.to_string().as_str() {
+                "lockup" => ("lockup", "Lockup", "LOCKUP_EXTENSION_NAME", true, true),
+                "lockup_nft" => (
+                    "lockup_nft",
+                    "LockupNft",
+                    "LOCKUP_NFT_EXTENSION_NAME",
+                    false,
+                    true,
+                ),
+                "force_unlock" => (
+                    "force_unlock",
+                    "ForceUnlock",
+                    "FORCE_UNLOCK_EXTENSION_NAME",
+                    true,
+                    false,
+                ),
+                "keeper" => ("keeper", "Keeper", "KEEPER_EXTENSION_NAME", true, true),
+                "strategies" => (
+                    "strategies",
+                    "Strategies",
+                    "STRATEGIES_EXTENSION_NAME",
+                    true,
+                    true,
+                ),
+                "harvest" => ("harvest", "Harvest", "HARVEST_EXTENSION_NAME", true, true),
+                "analytics" => (
+                    "analytics",
+                    "Analytics",
+                    "ANALYTICS_EXTENSION_NAME",
+                    true,
+                    true,
+                ),
+                "cw4626" => return Err(Error::new(
+                    ident.span(),
+                    "the cw4626 extension adds top level variants and cannot be used as a vault \
+                     extension",
+                )),
+                other => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!(
+                            "unknown extension `{other}`, custom extensions must be given as \
+                         `{other} = Prefix` or `{other}(execute = Path, query = Path)`"
+                        ),
+                    ))
+                }
+            };
+
+        let module = Ident::new(module, Span::call_site());
+        let msg_path = |suffix: &str| -> Path {
+            let msg = format_ident!("{}{}", prefix, suffix);
+            syn::parse_quote!(::cw_vault_standard::extensions::#module::#msg)
+        };
+        let name = Ident::new(name, Span::call_site());
+
+        Ok(Self {
+            execute: has_execute.then(|| msg_path("ExecuteMsg")),
+            query: has_query.then(|| msg_path("QueryMsg")),
+            info: quote! {
+                ::cw_vault_standard::ExtensionInfo::new(
+                    ::cw_vault_standard::extensions::#name,
+                    ::cw_vault_standard::VERSION,
+                )
+            },
+            ident,
+        })
+    }
+
+    fn custom(ident: Ident, execute: Option<Path>, query: Option<Path>) -> Self {
+        let name = ident.to_string().replace('_', "-");
+        Self {
+            execute,
+            query,
+            info: quote! {
+                ::cw_vault_standard::ExtensionInfo::new(#name, env!("CARGO_PKG_VERSION"))
+            },
+            ident,
+        }
+    }
+
+    /// Returns the name of the enum variant of the extension.
+    fn variant(&self) -> Ident {
+        let camel: String = self
+            .ident
+            .to_string()
+            .split('_')
+            .map(|part| {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            })
+            .collect();
+        Ident::new(&camel, self.ident.span())
+    }
+}
+
+/// Returns `prefix` with `suffix` appended to its last segment.
+fn with_suffix(prefix: &Path, suffix: &str) -> Result<Path> {
+    let mut path = prefix.clone();
+    let last = path
+        .segments
+        .last_mut()
+        .ok_or_else(|| Error::new_spanned(prefix, "expected a path"))?;
+    if !last.arguments.is_none() {
+        return Err(Error::new_spanned(
+            prefix,
+            "extension prefix cannot have generic arguments",
+        ));
+    }
+    last.ident = format_ident!("{}{}", last.ident, suffix);
+    Ok(path)
+}
+
+/// Generates an extension message enum with a variant and `From`
+/// implementation for each of the given extension messages.
+fn message_enum(
+    name: Ident,
+    variants: Vec<(Ident, &Path)>,
+    attrs: TokenStream2,
+    vis: &syn::Visibility,
+) -> TokenStream2 {
+    if variants.is_empty() {
+        return quote! {
+            #vis type #name = ::cosmwasm_std::Empty;
+        };
+    }
+
+    let doc = format!("The {name} of the vault, generated by `vault_extensions`.");
+    let idents = variants.iter().map(|(ident, _)| ident);
+    let paths = variants.iter().map(|(_, path)| path);
+    let from_impls = variants.iter().map(|(ident, path)| {
+        quote! {
+            impl ::core::convert::From<#path> for #name {
+                fn from(msg: #path) -> Self {
+                    #name::#ident(msg)
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #[::cosmwasm_schema::cw_serde]
+        #attrs
+        #vis enum #name {
+            #( #idents(#paths), )*
+        }
+
+        #( #from_impls )*
+    }
+}
+
+fn expand(args: ExtensionArgs, item: ItemStruct) -> Result<TokenStream2> {
+    if !matches!(item.fields, syn::Fields::Unit) {
+        return Err(Error::new_spanned(
+            &item,
+            "vault_extensions must be placed on a unit struct",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &item.generics,
+            "vault_extensions cannot be placed on a generic struct",
+        ));
+    }
+
+    let exts = args.0;
+    let vis = &item.vis;
+    let struct_name = &item.ident;
+
+    let execute_enum = message_enum(
+        Ident::new("ExtensionExecuteMsg", Span::call_site()),
+        exts.iter()
+            .filter_map(|ext| ext.execute.as_ref().map(|path| (ext.variant(), path)))
+            .collect(),
+        quote!(),
+        vis,
+    );
+    let query_enum = message_enum(
+        Ident::new("ExtensionQueryMsg", Span::call_site()),
+        exts.iter()
+            .filter_map(|ext| ext.query.as_ref().map(|path| (ext.variant(), path)))
+            .collect(),
+        quote! {
+            #[derive(::cosmwasm_schema::QueryResponses)]
+            #[query_responses(nested)]
+        },
+        vis,
+    );
+    let infos = exts.iter().map(|ext| &ext.info);
+
+    Ok(quote! {
+        #item
+
+        #execute_enum
+
+        #query_enum
+
+        impl #struct_name {
+            /// Returns the extensions used by the vault, for use in the
+            /// `VaultStandardInfo` query.
+            pub fn extensions() -> ::std::vec::Vec<::cw_vault_standard::ExtensionInfo> {
+                ::std::vec![ #( #infos ),* ]
+            }
+
+            /// Returns the `VaultStandardInfoResponse` of the vault.
+            pub fn vault_standard_info() -> ::cw_vault_standard::VaultStandardInfoResponse {
+                ::cw_vault_standard::VaultStandardInfoResponse {
+                    version: ::cw_vault_standard::VERSION.to_string(),
+                    extensions: Self::extensions(),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,97 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{from_json, to_json_string, Uint128};
+use cw_vault_standard::extensions::force_unlock::ForceUnlockExecuteMsg;
+use cw_vault_standard::extensions::lockup::{LockupExecuteMsg, LockupQueryMsg};
+use cw_vault_standard::{ExtensionInfo, VERSION};
+use cw_vault_standard_derive::vault_extensions;
+
+#[cw_serde]
+pub enum MyExtExecuteMsg {
+    Ping {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum MyExtQueryMsg {
+    #[returns(u64)]
+    Count {},
+}
+
+#[vault_extensions(lockup, force_unlock, my_ext = MyExt)]
+pub struct Extensions;
+
+#[test]
+fn extension_messages_are_wrapped_with_from() {
+    let unlock = LockupExecuteMsg::EmergencyUnlock {
+        amount: Uint128::one(),
+    };
+    assert_eq!(
+        ExtensionExecuteMsg::from(unlock.clone()),
+        ExtensionExecuteMsg::Lockup(unlock)
+    );
+    assert_eq!(
+        ExtensionExecuteMsg::from(MyExtExecuteMsg::Ping {}),
+        ExtensionExecuteMsg::MyExt(MyExtExecuteMsg::Ping {})
+    );
+    assert_eq!(
+        ExtensionQueryMsg::from(LockupQueryMsg::LockupDuration {}),
+        ExtensionQueryMsg::Lockup(LockupQueryMsg::LockupDuration {})
+    );
+    assert_eq!(
+        ExtensionQueryMsg::from(MyExtQueryMsg::Count {}),
+        ExtensionQueryMsg::MyExt(MyExtQueryMsg::Count {})
+    );
+}
+
+#[test]
+fn extension_messages_are_serialized_in_snake_case() {
+    let cases = [
+        (
+            ExtensionExecuteMsg::from(LockupExecuteMsg::EmergencyUnlock {
+                amount: Uint128::one(),
+            }),
+            r#"{"lockup":{"emergency_unlock":{"amount":"1"}}}"#,
+        ),
+        (
+            ExtensionExecuteMsg::from(ForceUnlockExecuteMsg::UpdateForceWithdrawWhitelist {
+                add_addresses: vec![],
+                remove_addresses: vec![],
+            }),
+            r#"{"force_unlock":{"update_force_withdraw_whitelist":{"add_addresses":[],"remove_addresses":[]}}}"#,
+        ),
+        (
+            ExtensionExecuteMsg::from(MyExtExecuteMsg::Ping {}),
+            r#"{"my_ext":{"ping":{}}}"#,
+        ),
+    ];
+    for (msg, json) in cases {
+        assert_eq!(to_json_string(&msg).unwrap(), json);
+        assert_eq!(from_json::<ExtensionExecuteMsg>(json).unwrap(), msg);
+    }
+
+    let msg = ExtensionQueryMsg::from(MyExtQueryMsg::Count {});
+    assert_eq!(to_json_string(&msg).unwrap(), r#"{"my_ext":{"count":{}}}"#);
+    from_json::<ExtensionExecuteMsg>(r#"{"my_ext":{"count":{}}}"#).unwrap_err();
+}
+
+#[test]
+fn query_responses_of_nested_messages_are_used() {
+    let responses = ExtensionQueryMsg::response_schemas().unwrap();
+    assert!(responses.contains_key("count"));
+    assert!(responses.contains_key("lockup_duration"));
+}
+
+#[test]
+fn extensions_use_canonical_names_and_versions() {
+    assert_eq!(
+        Extensions::extensions(),
+        vec![
+            ExtensionInfo::new("lockup", VERSION),
+            ExtensionInfo::new("force-unlock", VERSION),
+            ExtensionInfo::new("my-ext", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+    let info = Extensions::vault_standard_info();
+    assert_eq!(info.version, VERSION);
+    assert_eq!(info.extensions, Extensions::extensions());
+}
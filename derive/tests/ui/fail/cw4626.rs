@@ -0,0 +1,6 @@
+use cw_vault_standard_derive::vault_extensions;
+
+#[vault_extensions(cw4626)]
+pub struct Extensions;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use cw_vault_standard_derive::vault_extensions;
+
+#[vault_extensions(lockup, force_unlock, lockup)]
+pub struct Extensions;
+
+fn main() {}
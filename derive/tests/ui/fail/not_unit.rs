@@ -0,0 +1,8 @@
+use cw_vault_standard_derive::vault_extensions;
+
+#[vault_extensions(lockup)]
+pub struct Extensions {
+    pub lockup: bool,
+}
+
+fn main() {}
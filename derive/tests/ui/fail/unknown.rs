@@ -0,0 +1,6 @@
+use cw_vault_standard_derive::vault_extensions;
+
+#[vault_extensions(lockup, my_ext)]
+pub struct Extensions;
+
+fn main() {}
@@ -0,0 +1,42 @@
+use cw_vault_standard::extensions::force_unlock::ForceUnlockExecuteMsg;
+use cw_vault_standard::extensions::lockup::{LockupExecuteMsg, LockupQueryMsg};
+use cw_vault_standard::extensions::lockup_nft::LockupNftQueryMsg;
+use cw_vault_standard_derive::vault_extensions;
+
+#[vault_extensions(
+    lockup,
+    lockup_nft,
+    force_unlock,
+    keeper,
+    strategies,
+    harvest,
+    analytics
+)]
+pub struct Extensions;
+
+fn main() {
+    let _ = ExtensionExecuteMsg::Lockup(LockupExecuteMsg::WithdrawAllUnlocked { recipient: None });
+    let _ = ExtensionExecuteMsg::ForceUnlock(ForceUnlockExecuteMsg::UpdateForceWithdrawWhitelist {
+        add_addresses: vec![],
+        remove_addresses: vec![],
+    });
+    let _ = ExtensionQueryMsg::Lockup(LockupQueryMsg::LockupDuration {});
+    let _ = ExtensionQueryMsg::LockupNft(LockupNftQueryMsg::NftContract {});
+
+    let names: Vec<_> = Extensions::extensions()
+        .into_iter()
+        .map(|info| info.name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "lockup",
+            "lockup-nft",
+            "force-unlock",
+            "keeper",
+            "strategies",
+            "harvest",
+            "analytics"
+        ]
+    );
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::Empty;
+use cw_vault_standard_derive::vault_extensions;
+
+mod with_parens {
+    use super::*;
+
+    #[vault_extensions()]
+    pub struct Extensions;
+}
+
+#[vault_extensions]
+pub struct Extensions;
+
+fn main() {
+    let _: Empty = ExtensionExecuteMsg {};
+    let _: Empty = ExtensionQueryMsg {};
+    assert!(Extensions::extensions().is_empty());
+    assert!(with_parens::Extensions::extensions().is_empty());
+    assert_eq!(
+        Extensions::vault_standard_info().version,
+        cw_vault_standard::VERSION
+    );
+}
@@ -0,0 +1,36 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cw_vault_standard_derive::vault_extensions;
+
+#[cw_serde]
+pub enum PingMsg {
+    Ping {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum CountMsg {
+    #[returns(u64)]
+    Count {},
+}
+
+mod execute_only {
+    use super::*;
+
+    #[vault_extensions(my_ext(execute = PingMsg))]
+    pub struct Extensions;
+}
+
+mod both {
+    use super::*;
+
+    #[vault_extensions(my_ext(query = CountMsg, execute = PingMsg))]
+    pub struct Extensions;
+}
+
+fn main() {
+    let _ = execute_only::ExtensionExecuteMsg::MyExt(PingMsg::Ping {});
+    let _: cosmwasm_std::Empty = execute_only::ExtensionQueryMsg {};
+
+    let _ = both::ExtensionExecuteMsg::MyExt(PingMsg::Ping {});
+    let _ = both::ExtensionQueryMsg::MyExt(CountMsg::Count {});
+}
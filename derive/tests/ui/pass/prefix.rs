@@ -0,0 +1,27 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cw_vault_standard_derive::vault_extensions;
+
+mod my_ext {
+    use super::*;
+
+    #[cw_serde]
+    pub enum MyExtExecuteMsg {
+        Ping {},
+    }
+
+    #[cw_serde]
+    #[derive(QueryResponses)]
+    pub enum MyExtQueryMsg {
+        #[returns(u64)]
+        Count {},
+    }
+}
+
+#[vault_extensions(lockup, my_ext = my_ext::MyExt)]
+pub struct Extensions;
+
+fn main() {
+    let _ = ExtensionExecuteMsg::MyExt(my_ext::MyExtExecuteMsg::Ping {});
+    let _ = ExtensionQueryMsg::MyExt(my_ext::MyExtQueryMsg::Count {});
+    assert_eq!(Extensions::extensions()[1].name, "my-ext");
+}
@@ -3,7 +3,10 @@ use cw_it::helpers::Unwrap;
 use cw_it::test_tube::{Account, Runner, SigningAccount};
 
 use cw_utils::Duration;
-use cw_vault_standard::extensions::lockup::{LockupExecuteMsg, LockupQueryMsg, UnlockingPosition};
+use cw_vault_standard::extensions::lockup::{
+    ClaimableUnlockedResponse, EarlyWithdrawPreview, LockupExecuteMsg, LockupQueryMsg, LockupTier,
+    UnlockingPosition,
+};
 use cw_vault_standard::msg::VaultStandardExecuteMsg as ExecuteMsg;
 use cw_vault_standard::{ExtensionExecuteMsg, ExtensionQueryMsg, VaultStandardQueryMsg};
 
@@ -17,11 +20,24 @@ pub trait LockedVaultRobot<'a, R: Runner<'a> + 'a>: CwVaultStandardRobot<'a, R>
         funds: &[Coin],
         unwrap_choice: Unwrap,
         signer: &SigningAccount,
+    ) -> &Self {
+        self.unlock_in_tier_with_funds(amount, None, funds, unwrap_choice, signer)
+    }
+
+    /// Calls `ExecuteMsg::Unlock` with the given amount, lockup tier and funds.
+    fn unlock_in_tier_with_funds(
+        &self,
+        amount: impl Into<Uint128>,
+        tier: Option<u64>,
+        funds: &[Coin],
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
     ) -> &Self {
         unwrap_choice.unwrap(self.wasm().execute(
             &self.vault_addr(),
             &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(LockupExecuteMsg::Unlock {
                 amount: amount.into(),
+                tier,
             })),
             funds,
             signer,
@@ -74,6 +90,148 @@ pub trait LockedVaultRobot<'a, R: Runner<'a> + 'a>: CwVaultStandardRobot<'a, R>
         self
     }
 
+    /// Calls `ExecuteMsg::WithdrawAllUnlocked` to withdraw all matured lockup positions of the signer.
+    fn withdraw_all_unlocked(
+        &self,
+        recipient: Option<String>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::WithdrawAllUnlocked { recipient },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::WithdrawUnlockedBatch` to withdraw several lockup positions.
+    fn withdraw_unlocked_batch(
+        &self,
+        lockup_ids: Vec<u64>,
+        recipient: Option<String>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::WithdrawUnlockedBatch {
+                    lockup_ids,
+                    recipient,
+                },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::TransferUnlockingPosition` to transfer a lockup position to `recipient`.
+    fn transfer_unlocking_position(
+        &self,
+        lockup_id: u64,
+        recipient: impl Into<String>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::TransferUnlockingPosition {
+                    lockup_id,
+                    recipient: recipient.into(),
+                },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::SplitUnlockingPosition` to split `amount` base tokens off a lockup position.
+    fn split_unlocking_position(
+        &self,
+        lockup_id: u64,
+        amount: impl Into<Uint128>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::SplitUnlockingPosition {
+                    lockup_id,
+                    amount: amount.into(),
+                },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::MergeUnlockingPositions` to merge lockup positions into the first of `ids`.
+    fn merge_unlocking_positions(
+        &self,
+        ids: Vec<u64>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::MergeUnlockingPositions { ids },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::CancelUnlock` to turn a lockup position back into vault tokens.
+    fn cancel_unlock(
+        &self,
+        lockup_id: u64,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::CancelUnlock { lockup_id },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
+    /// Calls `ExecuteMsg::EarlyWithdraw` to withdraw a lockup position before it has finished unlocking.
+    fn early_withdraw(
+        &self,
+        lockup_id: u64,
+        recipient: Option<String>,
+        unwrap_choice: Unwrap,
+        signer: &SigningAccount,
+    ) -> &Self {
+        unwrap_choice.unwrap(self.wasm().execute(
+            &self.vault_addr(),
+            &ExecuteMsg::VaultExtension(ExtensionExecuteMsg::Lockup(
+                LockupExecuteMsg::EarlyWithdraw {
+                    lockup_id,
+                    recipient,
+                },
+            )),
+            &[],
+            signer,
+        ));
+        self
+    }
+
     /// Queries the vault for all unlocking positions of the given address (with optional pagination).
     fn query_unlocking_positions(
         &self,
@@ -95,6 +253,20 @@ pub trait LockedVaultRobot<'a, R: Runner<'a> + 'a>: CwVaultStandardRobot<'a, R>
             .unwrap()
     }
 
+    /// Queries the vault for the matured unlocking positions of the given address.
+    fn query_claimable_unlocked(&self, address: impl Into<String>) -> ClaimableUnlockedResponse {
+        self.wasm()
+            .query(
+                &self.vault_addr(),
+                &VaultStandardQueryMsg::VaultExtension(ExtensionQueryMsg::Lockup(
+                    LockupQueryMsg::ClaimableUnlocked {
+                        owner: address.into(),
+                    },
+                )),
+            )
+            .unwrap()
+    }
+
     /// Queries the vault for a single unlocking position.
     fn query_unlocking_position(&self, lockup_id: u64) -> UnlockingPosition {
         self.wasm()
@@ -119,6 +291,30 @@ pub trait LockedVaultRobot<'a, R: Runner<'a> + 'a>: CwVaultStandardRobot<'a, R>
             .unwrap()
     }
 
+    /// Queries the vault for its lockup tiers.
+    fn query_lockup_tiers(&self) -> Vec<LockupTier> {
+        self.wasm()
+            .query(
+                &self.vault_addr(),
+                &VaultStandardQueryMsg::VaultExtension(ExtensionQueryMsg::Lockup(
+                    LockupQueryMsg::LockupTiers {},
+                )),
+            )
+            .unwrap()
+    }
+
+    /// Queries the vault for the result of withdrawing a lockup position early.
+    fn query_preview_early_withdraw(&self, lockup_id: u64) -> EarlyWithdrawPreview {
+        self.wasm()
+            .query(
+                &self.vault_addr(),
+                &VaultStandardQueryMsg::VaultExtension(ExtensionQueryMsg::Lockup(
+                    LockupQueryMsg::PreviewEarlyWithdraw { lockup_id },
+                )),
+            )
+            .unwrap()
+    }
+
     /// Asserts that the number of unlocking positions in the vault is equal to the given value.
     fn assert_number_of_unlocking_positions(
         &self,
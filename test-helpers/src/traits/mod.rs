@@ -12,7 +12,7 @@ use cw_it::test_tube::{Account, Runner, SigningAccount};
 use cw_vault_standard::msg::{
     VaultStandardExecuteMsg as ExecuteMsg, VaultStandardQueryMsg as QueryMsg,
 };
-use cw_vault_standard::VaultInfoResponse;
+use cw_vault_standard::{VaultInfoResponse, VaultStandardInfoResponse};
 
 pub trait CwVaultStandardRobot<'a, R: Runner<'a> + 'a>: TestRobot<'a, R> {
     /// Returns the vault address.
@@ -25,6 +25,14 @@ pub trait CwVaultStandardRobot<'a, R: Runner<'a> + 'a>: TestRobot<'a, R> {
             .unwrap()
     }
 
+    /// Returns the version of the vault standard and the extensions used by
+    /// the vault.
+    fn query_vault_standard_info(&self) -> VaultStandardInfoResponse {
+        self.wasm()
+            .query(&self.vault_addr(), &QueryMsg::<Empty>::VaultStandardInfo {})
+            .unwrap()
+    }
+
     /// Returns the base token.
     fn base_token(&self) -> String {
         self.query_info().base_token
@@ -145,6 +153,14 @@ pub trait CwVaultStandardRobot<'a, R: Runner<'a> + 'a>: TestRobot<'a, R> {
 
     /////// ASSERTIONS ///////
 
+    /// Asserts that the vault reports the version of the vault standard that
+    /// these test helpers were compiled against.
+    fn assert_vault_standard_version_eq_crate_version(&self) -> &Self {
+        let info = self.query_vault_standard_info();
+        assert_eq!(info.version, cw_vault_standard::VERSION);
+        self
+    }
+
     /// Asserts that the base token balance of the given address is equal to the given amount.
     fn assert_base_token_balance_eq(
         &self,